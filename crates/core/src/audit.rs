@@ -0,0 +1,90 @@
+use crate::PersonaEntry;
+use std::fmt::Display;
+
+/// Renders `docs/PERSONA_AUDIT.md`: one table row per persona in the order given.
+pub fn render_persona_audit(entries: &[PersonaEntry]) -> String {
+    let mut output = String::new();
+    output.push_str("# Persona Audit\n\n");
+    output.push_str("This file is auto-generated by `cargo run -p personas-core --bin generate-persona-audit`.\n");
+    output.push_str(
+        "Do not edit it manually; instead, update persona metadata and rerun the generator.\n\n",
+    );
+    output.push_str("The table below summarizes the current Codex persona catalog.\n\n");
+    output.push_str("| Persona | ID | Description | Tags | Author | Created | Version |\n");
+    output.push_str("| ------ | -- | ----------- | ---- | ------ | ------- | ------- |\n");
+
+    for entry in entries {
+        let meta = &entry.meta;
+        output.push_str("| ");
+        output.push_str(&cell(&meta.name));
+        output.push_str(" | ");
+        output.push_str(&cell(&meta.id));
+        output.push_str(" | ");
+        output.push_str(&cell(meta.description.as_deref().unwrap_or("—")));
+        output.push_str(" | ");
+        output.push_str(&cell(&format_tags(meta.tags.as_ref())));
+        output.push_str(" | ");
+        output.push_str(&cell(meta.author.as_deref().unwrap_or("—")));
+        output.push_str(" | ");
+        output.push_str(&cell(&or_dash(meta.created_at)));
+        output.push_str(" | ");
+        output.push_str(&cell(&or_dash(meta.version)));
+        output.push_str(" |\n");
+    }
+
+    output.push_str("\n## Rating Scale\n\n");
+    output.push_str("- **High** — Immediately actionable with clear scope, concrete tools, and multiple example tasks.\n");
+    output.push_str("- **Medium-High** — Comprehensive overall, with minor future opportunities (e.g., domain templates or advanced playbooks) but already production ready.\n");
+    output.push_str("- **Medium** — Solid baseline instructions that still need richer scenarios or tool coverage before they become default-ready.\n");
+    output.push_str("- **Low** — Placeholder content lacking structure or actionable guidance.\n");
+
+    output
+}
+
+fn cell(value: &str) -> String {
+    value
+        .replace("\n", " ")
+        .replace('|', "\\|")
+        .trim()
+        .to_string()
+}
+
+fn or_dash<T: Display>(value: Option<T>) -> String {
+    value.map_or_else(|| "—".to_string(), |value| value.to_string())
+}
+
+fn format_tags(tags: Option<&Vec<String>>) -> String {
+    match tags {
+        Some(list) if !list.is_empty() => list.join(", "),
+        _ => "—".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PersonaMeta;
+
+    #[test]
+    fn renders_table_with_defaults() {
+        let entries = vec![PersonaEntry {
+            meta: PersonaMeta {
+                id: "analyst".into(),
+                name: "Discovery Analyst".into(),
+                description: Some("Aligns goals".into()),
+                tags: Some(vec!["analysis".into(), "strategy".into()]),
+                author: Some("QQRM".into()),
+                created_at: Some("2025-08-02".parse().expect("date")),
+                version: Some("0.2".parse().expect("version")),
+                extends: None,
+                extra: Default::default(),
+            },
+            uri: "https://example.invalid".into(),
+            root: "personas".into(),
+            handoffs: Vec::new(),
+        }];
+
+        let markdown = render_persona_audit(&entries);
+        assert!(markdown.contains("| Discovery Analyst | analyst | Aligns goals | analysis, strategy | QQRM | 2025-08-02 | 0.2 |"));
+    }
+}
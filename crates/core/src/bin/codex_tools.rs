@@ -0,0 +1,3 @@
+fn main() {
+    personas_core::cli::main(&[])
+}
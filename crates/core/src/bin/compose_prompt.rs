@@ -0,0 +1,5 @@
+//! Alias for `codex-tools compose`.
+
+fn main() {
+    personas_core::cli::main(&["compose"])
+}
@@ -0,0 +1,5 @@
+//! Alias for `codex-tools handoffs`.
+
+fn main() {
+    personas_core::cli::main(&["handoffs"])
+}
@@ -0,0 +1,608 @@
+use anyhow::{Context, Result, bail};
+use personas_core::{PersonaSource, RepoConfig, ScenarioSource};
+use serde_json::{Value, json};
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Component, Path, PathBuf, Prefix};
+
+const PROTOCOL_VERSION: &str = "2025-06-18";
+const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26", "2025-06-18"];
+const MARKDOWN_MIME_TYPE: &str = "text/markdown";
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<()> {
+    let args = Args::parse()?;
+    let repo_root = match args.repo_root {
+        Some(path) => path,
+        None => env::current_dir().context("determine repository root")?,
+    };
+    let server = Server::load(&repo_root)?;
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    server.serve(stdin.lock(), stdout.lock())
+}
+
+/// Personas and scenarios loaded from a repository checkout.
+struct Server {
+    personas: Vec<PersonaSource>,
+    resources: Vec<Resource>,
+}
+
+struct Resource {
+    uri: String,
+    id: String,
+    name: String,
+    description: Option<String>,
+    /// The Markdown as Pages publishes it: front matter, then the expanded, merged body.
+    text: String,
+}
+
+impl Server {
+    fn load(repo_root: &Path) -> Result<Self> {
+        let config = RepoConfig::load(repo_root, None).context("load configuration")?;
+        if !config.personas_dir().is_dir() {
+            bail!(
+                "personas directory missing: {}",
+                display(config.personas_dir())
+            );
+        }
+
+        let options = config.catalog_options();
+        let mut personas =
+            personas_core::collect::<PersonaSource>(&config.persona_roots, &options, None)
+                .into_result()
+                .with_context(|| {
+                    format!("collect personas from {}", display(config.personas_dir()))
+                })?;
+        personas.sort_by(|a, b| a.entry.meta.id.cmp(&b.entry.meta.id));
+
+        let mut resources = Vec::new();
+        for persona in &personas {
+            let meta = &persona.entry.meta;
+            resources.push(Resource {
+                uri: file_uri(&persona.path)?,
+                id: meta.id.clone(),
+                name: meta.name.clone(),
+                description: meta.description.clone(),
+                text: persona
+                    .published_markdown()
+                    .with_context(|| format!("render {}", display(&persona.path)))?,
+            });
+        }
+        if config.scenarios_dir().is_dir() {
+            let mut scenarios =
+                personas_core::collect::<ScenarioSource>(&config.scenario_roots, &options, None)
+                    .into_result()
+                    .with_context(|| {
+                        format!("collect scenarios from {}", display(config.scenarios_dir()))
+                    })?;
+            scenarios.sort_by(|a, b| a.entry.meta.id.cmp(&b.entry.meta.id));
+            for scenario in scenarios {
+                let text = scenario
+                    .published_markdown()
+                    .with_context(|| format!("render {}", display(&scenario.path)))?;
+                let meta = scenario.entry.meta;
+                resources.push(Resource {
+                    uri: file_uri(&scenario.path)?,
+                    id: meta.id,
+                    name: meta.name,
+                    description: meta.description,
+                    text,
+                });
+            }
+        }
+
+        Ok(Self {
+            personas,
+            resources,
+        })
+    }
+
+    fn serve<R: BufRead, W: Write>(&self, reader: R, mut writer: W) -> Result<()> {
+        for line in reader.lines() {
+            let line = line.context("read request from stdin")?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle_line(&line) {
+                writeln!(writer, "{response}").context("write response to stdout")?;
+                writer.flush().context("flush stdout")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_line(&self, line: &str) -> Option<Value> {
+        let message: Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(err) => {
+                return Some(error_response(
+                    Value::Null,
+                    PARSE_ERROR,
+                    &format!("parse error: {err}"),
+                ));
+            }
+        };
+
+        let Some(object) = message.as_object() else {
+            return Some(error_response(
+                Value::Null,
+                INVALID_REQUEST,
+                "request must be a JSON object",
+            ));
+        };
+        let id = object.get("id").cloned();
+        let Some(method) = object.get("method").and_then(Value::as_str) else {
+            // Responses from the client (for example to pings) carry no method.
+            return id.map(|id| error_response(id, INVALID_REQUEST, "missing method"));
+        };
+        let params = object.get("params").cloned().unwrap_or(Value::Null);
+
+        // Notifications never receive a response, even when they fail.
+        let id = id?;
+        let response = match self.dispatch(method, &params) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(err) => error_response(id, err.code, &err.message),
+        };
+        Some(response)
+    }
+
+    fn dispatch(&self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => Ok(self.initialize(params)),
+            "ping" => Ok(json!({})),
+            "prompts/list" => Ok(self.list_prompts()),
+            "prompts/get" => self.get_prompt(params),
+            "resources/list" => Ok(self.list_resources()),
+            "resources/read" => self.read_resource(params),
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("method not found: {method}"),
+            )),
+        }
+    }
+
+    fn initialize(&self, params: &Value) -> Value {
+        let requested = params.get("protocolVersion").and_then(Value::as_str);
+        let protocol_version = requested
+            .filter(|version| SUPPORTED_PROTOCOL_VERSIONS.contains(version))
+            .unwrap_or(PROTOCOL_VERSION);
+        json!({
+            "protocolVersion": protocol_version,
+            "capabilities": {
+                "prompts": { "listChanged": false },
+                "resources": { "listChanged": false },
+            },
+            "serverInfo": {
+                "name": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        })
+    }
+
+    fn list_prompts(&self) -> Value {
+        let prompts = self
+            .personas
+            .iter()
+            .map(|persona| {
+                let meta = &persona.entry.meta;
+                let mut prompt = json!({
+                    "name": meta.id,
+                    "title": meta.name,
+                    "arguments": [],
+                });
+                if let Some(description) = &meta.description {
+                    prompt["description"] = json!(description);
+                }
+                prompt
+            })
+            .collect::<Vec<_>>();
+        json!({ "prompts": prompts })
+    }
+
+    fn get_prompt(&self, params: &Value) -> Result<Value, RpcError> {
+        let name = required_str(params, "name")?;
+        let persona = self
+            .personas
+            .iter()
+            .find(|persona| persona.entry.meta.id == name)
+            .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("unknown prompt: {name}")))?;
+        let meta = &persona.entry.meta;
+        Ok(json!({
+            "description": meta.description.as_deref().unwrap_or(&meta.name),
+            "messages": [
+                {
+                    "role": "user",
+                    "content": { "type": "text", "text": persona.body },
+                }
+            ],
+        }))
+    }
+
+    fn list_resources(&self) -> Value {
+        let resources = self
+            .resources
+            .iter()
+            .map(|resource| {
+                let mut value = json!({
+                    "uri": resource.uri,
+                    "name": resource.id,
+                    "title": resource.name,
+                    "mimeType": MARKDOWN_MIME_TYPE,
+                });
+                if let Some(description) = &resource.description {
+                    value["description"] = json!(description);
+                }
+                value
+            })
+            .collect::<Vec<_>>();
+        json!({ "resources": resources })
+    }
+
+    fn read_resource(&self, params: &Value) -> Result<Value, RpcError> {
+        let uri = required_str(params, "uri")?;
+        let resource = self
+            .resources
+            .iter()
+            .find(|resource| resource.uri == uri)
+            .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("unknown resource: {uri}")))?;
+        Ok(json!({
+            "contents": [
+                { "uri": resource.uri, "mimeType": MARKDOWN_MIME_TYPE, "text": resource.text }
+            ],
+        }))
+    }
+}
+
+fn file_uri(path: &Path) -> Result<String> {
+    let absolute = fs::canonicalize(path).with_context(|| format!("resolve {}", display(path)))?;
+    Ok(path_uri(&absolute))
+}
+
+/// The `file:` URI of an absolute path, with each component percent-encoded. A Windows
+/// drive becomes the first segment (`file:///C:/...`) and a UNC share the authority
+/// (`file://server/share/...`).
+fn path_uri(absolute: &Path) -> String {
+    let mut authority = String::new();
+    let mut segments = Vec::new();
+    for component in absolute.components() {
+        match component {
+            Component::Prefix(prefix) => match prefix.kind() {
+                Prefix::Disk(drive) | Prefix::VerbatimDisk(drive) => {
+                    segments.push(format!("{}:", char::from(drive)));
+                }
+                Prefix::UNC(server, share) | Prefix::VerbatimUNC(server, share) => {
+                    authority = percent_encode(&server.to_string_lossy());
+                    segments.push(percent_encode(&share.to_string_lossy()));
+                }
+                _ => segments.push(percent_encode(&prefix.as_os_str().to_string_lossy())),
+            },
+            Component::Normal(part) => segments.push(percent_encode(&part.to_string_lossy())),
+            Component::RootDir | Component::CurDir | Component::ParentDir => {}
+        }
+    }
+    format!("file://{authority}/{}", segments.join("/"))
+}
+
+/// Escapes every byte of `segment` outside the URI unreserved set.
+fn percent_encode(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+fn required_str<'a>(params: &'a Value, key: &str) -> Result<&'a str, RpcError> {
+    params
+        .get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("missing string param `{key}`")))
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: String) -> Self {
+        Self { code, message }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct Args {
+    repo_root: Option<PathBuf>,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        Self::parse_from(env::args().skip(1))
+    }
+
+    fn parse_from<I>(args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut repo_root = None;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--repo-root" => {
+                    let Some(value) = args.next() else {
+                        bail!("--repo-root requires a path");
+                    };
+                    repo_root = Some(PathBuf::from(value));
+                }
+                _ => bail!("unknown argument: {arg}"),
+            }
+        }
+        Ok(Self { repo_root })
+    }
+}
+
+fn display(path: &Path) -> String {
+    path.display().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn repo() -> tempfile::TempDir {
+        let tmp = tempdir().expect("tempdir");
+        let personas_dir = tmp.path().join("personas");
+        let scenarios_dir = tmp.path().join("scenarios");
+        fs::create_dir(&personas_dir).expect("personas dir");
+        fs::create_dir(&scenarios_dir).expect("scenarios dir");
+        fs::write(
+            personas_dir.join("ONE.md"),
+            "---\nid: one\nname: One\ndescription: First\n---\n# One\n",
+        )
+        .expect("persona");
+        fs::write(
+            scenarios_dir.join("AUDIT.md"),
+            "---\nid: audit\nname: Audit\n---\n# Audit\n",
+        )
+        .expect("scenario");
+        tmp
+    }
+
+    fn call(server: &Server, request: Value) -> Value {
+        server
+            .handle_line(&request.to_string())
+            .expect("response expected")
+    }
+
+    #[test]
+    fn initialize_negotiates_supported_version() {
+        let tmp = repo();
+        let server = Server::load(tmp.path()).expect("server");
+        let response = call(
+            &server,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"protocolVersion": "2024-11-05"}}),
+        );
+        assert_eq!(response["result"]["protocolVersion"], "2024-11-05");
+        assert!(response["result"]["capabilities"]["prompts"].is_object());
+    }
+
+    #[test]
+    fn lists_and_gets_persona_prompts() {
+        let tmp = repo();
+        let server = Server::load(tmp.path()).expect("server");
+        let list = call(
+            &server,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "prompts/list"}),
+        );
+        assert_eq!(list["result"]["prompts"][0]["name"], "one");
+        assert_eq!(list["result"]["prompts"][0]["description"], "First");
+
+        let prompt = call(
+            &server,
+            json!({"jsonrpc": "2.0", "id": 2, "method": "prompts/get", "params": {"name": "one"}}),
+        );
+        assert_eq!(
+            prompt["result"]["messages"][0]["content"]["text"],
+            "# One\n"
+        );
+    }
+
+    #[test]
+    fn file_uris_percent_encode_each_component() {
+        assert_eq!(
+            path_uri(Path::new("/srv/my repo/personas/Café#1.md")),
+            "file:///srv/my%20repo/personas/Caf%C3%A9%231.md"
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn file_uris_keep_the_windows_drive_and_share() {
+        assert_eq!(
+            path_uri(Path::new(r"\\?\C:\repo\personas\ONE.md")),
+            "file:///C:/repo/personas/ONE.md"
+        );
+        assert_eq!(
+            path_uri(Path::new(r"\\server\share\personas\ONE.md")),
+            "file://server/share/personas/ONE.md"
+        );
+    }
+
+    #[test]
+    fn lists_and_reads_persona_and_scenario_resources() {
+        let tmp = repo();
+        fs::create_dir(tmp.path().join("snippets")).expect("snippets");
+        fs::write(tmp.path().join("snippets/steps.md"), "1. Check.\n").expect("snippet");
+        fs::write(
+            tmp.path().join("scenarios/AUDIT.md"),
+            "---\nid: audit\nname: Audit\n---\n# Audit\n{{> snippets/steps.md}}\n",
+        )
+        .expect("scenario");
+        let server = Server::load(tmp.path()).expect("server");
+        let list = call(
+            &server,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "resources/list"}),
+        );
+        let resources = list["result"]["resources"].as_array().expect("resources");
+        let names = resources
+            .iter()
+            .map(|resource| resource["name"].as_str().expect("name"))
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["one", "audit"]);
+
+        let uri = resources[1]["uri"].as_str().expect("uri");
+        assert!(uri.starts_with("file:///"));
+        assert!(uri.ends_with("/scenarios/AUDIT.md"));
+        let read = call(
+            &server,
+            json!({"jsonrpc": "2.0", "id": 2, "method": "resources/read", "params": {"uri": uri}}),
+        );
+        let text = read["result"]["contents"][0]["text"]
+            .as_str()
+            .expect("text");
+        assert!(text.starts_with("---\nid: audit"));
+        assert!(text.ends_with("# Audit\n1. Check.\n"), "{text}");
+    }
+
+    #[test]
+    fn extended_personas_are_read_with_their_resolved_front_matter() {
+        let tmp = repo();
+        fs::write(
+            tmp.path().join("personas/LOCAL.md"),
+            "---\nid: local\nname: Local\nextends: one\n---\n# Local\n",
+        )
+        .expect("persona");
+        let server = Server::load(tmp.path()).expect("server");
+        let resource = server
+            .resources
+            .iter()
+            .find(|resource| resource.id == "local")
+            .expect("local resource");
+        let read = call(
+            &server,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "resources/read", "params": {"uri": resource.uri}}),
+        );
+        assert_eq!(
+            read["result"]["contents"][0]["text"],
+            "---\nid: local\nname: Local\ndescription: First\n---\n# Local\n"
+        );
+    }
+
+    #[test]
+    fn loads_roots_from_the_repository_configuration() {
+        let tmp = repo();
+        fs::create_dir_all(tmp.path().join("private/personas")).expect("overlay");
+        fs::write(
+            tmp.path().join("private/personas/TWO.md"),
+            "---\nid: two\nname: Two\n---\n# Two\n",
+        )
+        .expect("persona");
+        fs::write(
+            tmp.path().join("codex-tools.yaml"),
+            "personas: [personas, private/personas]\n",
+        )
+        .expect("config");
+        let server = Server::load(tmp.path()).expect("server");
+        let ids = server
+            .personas
+            .iter()
+            .map(|persona| persona.entry.meta.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["one", "two"]);
+    }
+
+    #[test]
+    fn reports_json_rpc_errors() {
+        let tmp = repo();
+        let server = Server::load(tmp.path()).expect("server");
+
+        let parse = server.handle_line("{not json").expect("response");
+        assert_eq!(parse["error"]["code"], PARSE_ERROR);
+
+        let unknown = call(
+            &server,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}),
+        );
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+
+        let missing = call(
+            &server,
+            json!({"jsonrpc": "2.0", "id": 2, "method": "prompts/get", "params": {"name": "nope"}}),
+        );
+        assert_eq!(missing["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn notifications_receive_no_response() {
+        let tmp = repo();
+        let server = Server::load(tmp.path()).expect("server");
+        let response = server.handle_line(
+            &json!({"jsonrpc": "2.0", "method": "notifications/initialized"}).to_string(),
+        );
+        assert!(response.is_none());
+    }
+
+    #[test]
+    fn serve_answers_each_request_line() {
+        let tmp = repo();
+        let server = Server::load(tmp.path()).expect("server");
+        let input = concat!(
+            r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#,
+            "\n",
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+            "\n",
+            r#"{"jsonrpc":"2.0","id":2,"method":"prompts/list"}"#,
+            "\n",
+        );
+        let mut output = Vec::new();
+        server.serve(input.as_bytes(), &mut output).expect("serve");
+        let lines = String::from_utf8(output).expect("utf8");
+        let responses = lines
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).expect("json"))
+            .collect::<Vec<_>>();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[1]["id"], 2);
+    }
+
+    #[test]
+    fn args_parse_from_accepts_repo_root() {
+        let parsed =
+            Args::parse_from(vec!["--repo-root".into(), "/tmp/repo".into()]).expect("args");
+        assert_eq!(parsed.repo_root, Some(PathBuf::from("/tmp/repo")));
+    }
+
+    #[test]
+    fn args_parse_from_rejects_unknown_flag() {
+        let err = Args::parse_from(vec!["--unknown".into()]).unwrap_err();
+        assert!(err.to_string().contains("unknown argument"));
+    }
+}
@@ -0,0 +1,5 @@
+//! Alias for `codex-tools render`.
+
+fn main() {
+    personas_core::cli::main(&["render"])
+}
@@ -0,0 +1,5 @@
+//! Alias for `codex-tools serve`.
+
+fn main() {
+    personas_core::cli::main(&["serve"])
+}
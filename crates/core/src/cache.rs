@@ -0,0 +1,289 @@
+use crate::{CatalogError, MarkdownSource, PersonaMeta, RepoConfig, ScenarioMeta};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Parsed persona and scenario files kept between collections, so a watch loop only
+/// re-parses the files that changed.
+///
+/// A file is reused while its modification time and size, and those of the snippets it
+/// includes, are unchanged. Use one cache per set of roots and [`crate::CatalogOptions`].
+#[derive(Default)]
+pub struct SourceCache {
+    pub(crate) personas: FileCache<PersonaMeta>,
+    pub(crate) scenarios: FileCache<ScenarioMeta>,
+}
+
+impl SourceCache {
+    /// How many files the most recent persona and scenario collections parsed.
+    pub fn parsed(&self) -> usize {
+        self.personas.parsed + self.scenarios.parsed
+    }
+
+    /// The snippets included by the cached sources, stamped when those sources were parsed.
+    pub(crate) fn includes(&self) -> Snapshot {
+        let personas = self.personas.entries.values().map(|entry| &entry.includes);
+        let scenarios = self.scenarios.entries.values().map(|entry| &entry.includes);
+        Snapshot(
+            personas
+                .chain(scenarios)
+                .flat_map(|includes| includes.0.clone())
+                .collect(),
+        )
+    }
+}
+
+/// What identifies one version of a file without reading it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Stamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl Stamp {
+    pub(crate) fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
+}
+
+/// The inputs of a generation run, each with its stamp or `None` when it could not be read.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Snapshot(BTreeMap<PathBuf, Option<Stamp>>);
+
+impl Snapshot {
+    /// Stamps the Markdown files under every persona and scenario root, `AGENTS.md`,
+    /// and `extra`.
+    ///
+    /// Generated files such as `catalog.json` are not Markdown, so writing them never
+    /// counts as a change.
+    pub(crate) fn take(config: &RepoConfig, extra: &[PathBuf]) -> Self {
+        let roots = config.persona_roots.iter().chain(&config.scenario_roots);
+        let paths = roots
+            .flat_map(|root| crate::markdown_paths(root).unwrap_or_default())
+            .chain([config.agents_path.clone()])
+            .chain(extra.iter().cloned());
+        Self::of(paths)
+    }
+
+    fn of(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self(
+            paths
+                .into_iter()
+                .map(|path| {
+                    let stamp = Stamp::of(&path);
+                    (path, stamp)
+                })
+                .collect(),
+        )
+    }
+
+    /// The same files, stamped again.
+    pub(crate) fn refresh(&self) -> Self {
+        Self::of(self.0.keys().cloned())
+    }
+
+    /// Files added, removed, or modified since `earlier`, in path order.
+    pub(crate) fn changes_since(&self, earlier: &Self) -> Vec<PathBuf> {
+        let mut changes = self
+            .0
+            .iter()
+            .filter(|(path, stamp)| earlier.0.get(*path) != Some(stamp))
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        changes.extend(
+            earlier
+                .0
+                .keys()
+                .filter(|path| !self.0.contains_key(*path))
+                .cloned(),
+        );
+        changes.sort();
+        changes
+    }
+}
+
+struct Entry<M> {
+    stamp: Stamp,
+    includes: Snapshot,
+    source: MarkdownSource<M>,
+}
+
+pub(crate) struct FileCache<M> {
+    entries: HashMap<PathBuf, Entry<M>>,
+    /// Paths looked up since [`FileCache::start`].
+    seen: HashSet<PathBuf>,
+    parsed: usize,
+}
+
+impl<M> Default for FileCache<M> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            seen: HashSet::new(),
+            parsed: 0,
+        }
+    }
+}
+
+impl<M: Clone> FileCache<M> {
+    /// Begins a collection.
+    pub(crate) fn start(&mut self) {
+        self.seen.clear();
+        self.parsed = 0;
+    }
+
+    /// The cached source for `path` when the file is unchanged, otherwise the result of `read`.
+    pub(crate) fn read(
+        &mut self,
+        path: &Path,
+        read: impl FnOnce() -> Result<MarkdownSource<M>, CatalogError>,
+    ) -> Result<MarkdownSource<M>, CatalogError> {
+        self.seen.insert(path.to_path_buf());
+        let stamp = Stamp::of(path);
+        if let Some(entry) = self.entries.get(path)
+            && Some(entry.stamp) == stamp
+            && entry.includes.refresh() == entry.includes
+        {
+            return Ok(entry.source.clone());
+        }
+
+        self.parsed += 1;
+        self.entries.remove(path);
+        let source = read()?;
+        if let Some(stamp) = stamp {
+            let entry = Entry {
+                stamp,
+                includes: Snapshot::of(source.includes.iter().cloned()),
+                source: source.clone(),
+            };
+            self.entries.insert(path.to_path_buf(), entry);
+        }
+        Ok(source)
+    }
+
+    /// Ends a collection, forgetting files that were not looked up, such as deleted ones.
+    pub(crate) fn finish(&mut self) {
+        let seen = &self.seen;
+        self.entries.retain(|path, _| seen.contains(path));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CatalogOptions, PersonaSource, ScenarioSource, collect};
+    use tempfile::tempdir;
+
+    #[test]
+    fn reparses_only_changed_files() {
+        let tmp = tempdir().expect("tempdir");
+        let personas = tmp.path().join("personas");
+        fs::create_dir(&personas).expect("personas");
+        fs::create_dir(tmp.path().join("snippets")).expect("snippets");
+        fs::write(tmp.path().join("snippets/loop.md"), "- Test.\n").expect("snippet");
+        fs::write(
+            personas.join("ONE.md"),
+            "---\nid: one\nname: One\n---\n# One\n",
+        )
+        .expect("persona");
+        fs::write(
+            personas.join("TWO.md"),
+            "---\nid: two\nname: Two\n---\n# Two\n{{> snippets/loop.md}}\n",
+        )
+        .expect("persona");
+        let roots = [personas.clone()];
+        let options = CatalogOptions::default();
+        let mut cache = SourceCache::default();
+
+        let report = collect::<PersonaSource>(&roots, &options, Some(&mut cache));
+        assert_eq!((report.sources.len(), cache.parsed()), (2, 2));
+
+        let report = collect::<PersonaSource>(&roots, &options, Some(&mut cache));
+        assert_eq!((report.sources.len(), cache.parsed()), (2, 0));
+        let snippet = tmp.path().join("snippets/loop.md");
+        assert_eq!(cache.includes().0.keys().collect::<Vec<_>>(), [&snippet]);
+
+        // Editing the snippet re-parses TWO, which includes it, but not ONE.
+        fs::write(&snippet, "- Ship the release.\n").expect("snippet");
+        let report = collect::<PersonaSource>(&roots, &options, Some(&mut cache));
+        assert_eq!(cache.parsed(), 1);
+        assert!(report.sources[1].body.contains("- Ship the release."));
+
+        fs::write(
+            personas.join("ONE.md"),
+            "---\nid: one\nname: Uno\n---\n# Uno\n",
+        )
+        .expect("persona");
+        let report = collect::<PersonaSource>(&roots, &options, Some(&mut cache));
+        assert_eq!(cache.parsed(), 1);
+        assert_eq!(report.sources[0].entry.meta.name, "Uno");
+
+        fs::remove_file(personas.join("ONE.md")).expect("remove");
+        let report = collect::<PersonaSource>(&roots, &options, Some(&mut cache));
+        assert_eq!(report.sources.len(), 1);
+        assert!(
+            !cache
+                .personas
+                .entries
+                .contains_key(&personas.join("ONE.md"))
+        );
+
+        let roots = [tmp.path().join("scenarios")];
+        let report = collect::<ScenarioSource>(&roots, &options, Some(&mut cache));
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(cache.scenarios.parsed, 0);
+    }
+
+    #[test]
+    fn snapshots_report_source_changes_but_not_outputs() {
+        let tmp = tempdir().expect("tempdir");
+        for dir in ["personas", "scenarios", "docs"] {
+            fs::create_dir(tmp.path().join(dir)).expect("dir");
+        }
+        fs::write(tmp.path().join("AGENTS.md"), "# Agents\n").expect("agents");
+        fs::write(tmp.path().join("personas/ONE.md"), "one").expect("persona");
+        fs::write(tmp.path().join("scenarios/AUDIT.md"), "audit").expect("scenario");
+        fs::write(tmp.path().join("docs/EXTRA.md"), "extra").expect("extra");
+        let config = RepoConfig::defaults(tmp.path());
+        let extra = [tmp.path().join("docs/EXTRA.md")];
+        let before = Snapshot::take(&config, &extra);
+        assert_eq!(before.0.len(), 4);
+
+        fs::write(tmp.path().join("personas/catalog.json"), "{}").expect("catalog");
+        fs::write(tmp.path().join("docs/PERSONA_AUDIT.md"), "audit").expect("audit");
+        assert_eq!(Snapshot::take(&config, &extra), before);
+
+        fs::write(tmp.path().join("personas/TWO.md"), "new").expect("persona");
+        fs::remove_file(tmp.path().join("scenarios/AUDIT.md")).expect("remove");
+        fs::write(tmp.path().join("AGENTS.md"), "# Agents, edited\n").expect("agents");
+        fs::write(tmp.path().join("docs/EXTRA.md"), "edited").expect("extra");
+        assert_eq!(
+            Snapshot::take(&config, &extra).changes_since(&before),
+            [
+                tmp.path().join("AGENTS.md"),
+                tmp.path().join("docs/EXTRA.md"),
+                tmp.path().join("personas/TWO.md"),
+                tmp.path().join("scenarios/AUDIT.md"),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_files_are_parsed_until_fixed() {
+        let tmp = tempdir().expect("tempdir");
+        fs::write(tmp.path().join("ONE.md"), "no front matter\n").expect("persona");
+        let roots = [tmp.path().to_path_buf()];
+        let options = CatalogOptions::default();
+        let mut cache = SourceCache::default();
+
+        for _ in 0..2 {
+            let report = collect::<PersonaSource>(&roots, &options, Some(&mut cache));
+            assert_eq!((report.errors.len(), cache.parsed()), (1, 1));
+        }
+    }
+}
@@ -0,0 +1,682 @@
+//! The `codex-tools` command line: one entry point for every generator and check.
+//!
+//! `generate-catalog`, `generate-persona-audit`, `generate-handoff-graph`, `compose-prompt`,
+//! `render-markdown`, and `serve` are aliases that run [`main`] with their subcommand prepended.
+
+use crate::{Diagnostic, PersonaEntry, RepoConfig, SortOrder};
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+mod browse;
+mod catalog;
+mod pages;
+mod reports;
+mod serve;
+mod watch;
+
+/// The command finished and every checked output was up to date.
+pub const EXIT_SUCCESS: i32 = 0;
+/// Sources had errors or an I/O operation failed.
+pub const EXIT_FAILURE: i32 = 1;
+/// The command line could not be parsed.
+pub const EXIT_USAGE: i32 = 2;
+/// `--check` found a generated file that differs from what its sources produce.
+pub const EXIT_STALE: i32 = 3;
+
+pub const USAGE: &str = "\
+usage: codex-tools [options] <command> [command options]
+
+commands:
+  catalog                 regenerate personas/catalog.json and scenarios/catalog.json
+  lint                    validate persona and scenario sources without writing anything
+  audit                   regenerate docs/PERSONA_AUDIT.md
+  watch [--interval <ms>] regenerate both catalogs and the audit whenever a persona,
+                          scenario, or AGENTS.md changes (default interval: 500)
+  handoffs                regenerate docs/PERSONA_HANDOFFS.md and PERSONA_HANDOFFS.dot
+  show <id>               print a persona or scenario with templates expanded
+  search <term>...        list personas and scenarios matching every term
+  compose --persona <id> [--scenario <id>] [--output <file>]
+                          compose AGENTS.md, a persona, and a scenario into one prompt
+  render --output <dir>   write expanded persona and scenario Markdown
+  pages build [--output <dir>]
+  pages validate [--output <dir>]
+                          build or validate the GitHub Pages artifact (default: public)
+  serve [--output <dir> | --live] [--host <addr>] [--port <n>]
+                          preview the artifact, or the repository with --live, over HTTP
+                          (default: 127.0.0.1:8000)
+
+options (accepted before or after the command, up to a `--`):
+  --repo-root <dir>       repository to operate on (default: current directory)
+  --config <file>         settings file (default: codex-tools.yaml when present)
+  --check                 compare generated files instead of writing them
+  --quiet                 suppress progress messages
+  --format text|json      output format (default: text)
+  --persona-root <dir>    layer another persona directory over the configured ones
+  --scenario-root <dir>   layer another scenario directory over the configured ones
+  --strict                reject unknown front matter keys that look like typos
+  --namespace-ids         prefix ids of nested files with their folder path
+  --pages-base-url <url>  published site root used in catalog URIs
+  --sort id|name          catalog entry order
+  --message-format human|machine
+                          diagnostic style for catalog and lint
+
+exit codes: 0 success, 1 errors, 2 invalid usage, 3 stale outputs with --check
+";
+
+/// Runs `codex-tools` with `prefix` followed by the process arguments, then exits.
+pub fn main(prefix: &[&str]) -> ! {
+    let args = prefix
+        .iter()
+        .map(|arg| arg.to_string())
+        .chain(env::args().skip(1));
+    let code = run(args, &mut io::stdout(), &mut io::stderr());
+    std::process::exit(code)
+}
+
+/// Runs `codex-tools` with `args` (without the program name) and returns the exit code.
+///
+/// Command results go to `out`; progress messages, diagnostics, and errors go to `err`.
+pub fn run<I>(args: I, out: &mut dyn Write, err: &mut dyn Write) -> i32
+where
+    I: IntoIterator<Item = String>,
+{
+    let invocation = match Invocation::parse_from(args) {
+        Ok(invocation) => invocation,
+        Err(error) => {
+            let _ = writeln!(err, "error: {error}\nRun `codex-tools --help` for usage.");
+            return EXIT_USAGE;
+        }
+    };
+    match invocation.run(out, err) {
+        Ok(()) => EXIT_SUCCESS,
+        Err(error) => {
+            let _ = writeln!(err, "error: {error:#}");
+            if error.is::<StaleOutputs>() {
+                EXIT_STALE
+            } else {
+                EXIT_FAILURE
+            }
+        }
+    }
+}
+
+/// The error behind [`EXIT_STALE`].
+#[derive(Debug, thiserror::Error)]
+#[error("{} out of date. Run `cargo run -p personas-core --bin codex-tools -- {command}` and commit the changes.", display_all(.paths))]
+struct StaleOutputs {
+    command: &'static str,
+    paths: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+impl Format {
+    fn parse(value: &str) -> Result<Self> {
+        match value {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => bail!("unknown format: {value} (expected `text` or `json`)"),
+        }
+    }
+}
+
+/// Options shared by every command.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct GlobalArgs {
+    repo_root: Option<PathBuf>,
+    config: Option<PathBuf>,
+    check: bool,
+    quiet: bool,
+    format: Format,
+    message_format: MessageFormat,
+    strict: bool,
+    namespace_ids: bool,
+    /// Extra persona directories layered over the configured ones, lowest precedence first.
+    persona_overlays: Vec<PathBuf>,
+    scenario_overlays: Vec<PathBuf>,
+    pages_base_url: Option<String>,
+    sort: Option<SortOrder>,
+}
+
+impl GlobalArgs {
+    /// Consumes `arg` (and its value from `args`) when it is a global option.
+    fn parse_flag(&mut self, arg: &str, args: &mut dyn Iterator<Item = String>) -> Result<bool> {
+        let mut value = |what: &str| {
+            args.next()
+                .with_context(|| format!("{arg} requires {what}"))
+        };
+        match arg {
+            "--repo-root" => self.repo_root = Some(PathBuf::from(value("a path")?)),
+            "--config" => self.config = Some(PathBuf::from(value("a file")?)),
+            "--check" => self.check = true,
+            "--quiet" | "-q" => self.quiet = true,
+            "--format" => self.format = Format::parse(&value("`text` or `json`")?)?,
+            "--message-format" => {
+                self.message_format = MessageFormat::parse(&value("`human` or `machine`")?)?
+            }
+            "--strict" => self.strict = true,
+            "--namespace-ids" => self.namespace_ids = true,
+            "--persona-root" => self
+                .persona_overlays
+                .push(PathBuf::from(value("a directory")?)),
+            "--scenario-root" => self
+                .scenario_overlays
+                .push(PathBuf::from(value("a directory")?)),
+            "--pages-base-url" => self.pages_base_url = Some(value("a URL")?),
+            "--sort" => {
+                self.sort = Some(
+                    value("`id` or `name`")?
+                        .parse()
+                        .map_err(anyhow::Error::msg)?,
+                )
+            }
+            _ => {
+                if let Some(format) = arg.strip_prefix("--format=") {
+                    self.format = Format::parse(format)?;
+                } else if let Some(style) = arg.strip_prefix("--message-format=") {
+                    self.message_format = MessageFormat::parse(style)?;
+                } else {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Loads the repository settings and layers the command-line flags over them.
+    fn load_config(&self, repo_root: &Path) -> Result<RepoConfig> {
+        let mut config =
+            RepoConfig::load(repo_root, self.config.as_deref()).context("load configuration")?;
+        config.strict |= self.strict;
+        config.namespace_ids |= self.namespace_ids;
+        config.persona_roots.extend(
+            self.persona_overlays
+                .iter()
+                .map(|overlay| repo_root.join(overlay)),
+        );
+        config.scenario_roots.extend(
+            self.scenario_overlays
+                .iter()
+                .map(|overlay| repo_root.join(overlay)),
+        );
+        if let Some(url) = &self.pages_base_url {
+            config.pages_base_url = url.clone();
+        }
+        if let Some(sort) = self.sort {
+            config.sort = sort;
+        }
+        Ok(config)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Command {
+    Help,
+    Catalog,
+    Lint,
+    Audit,
+    Handoffs,
+    Show(browse::ShowArgs),
+    Search(browse::SearchArgs),
+    Compose(browse::ComposeArgs),
+    Render(pages::RenderArgs),
+    PagesBuild(pages::PagesArgs),
+    PagesValidate(pages::PagesArgs),
+    Serve(serve::ServeArgs),
+    Watch(watch::WatchArgs),
+}
+
+impl Command {
+    fn parse_from(args: Vec<String>) -> Result<Self> {
+        let mut args = args.into_iter();
+        let Some(name) = args.next() else {
+            bail!("missing command");
+        };
+        let rest = args.collect::<Vec<_>>();
+        let command = match name.as_str() {
+            "help" | "--help" | "-h" => Self::Help,
+            "catalog" => no_arguments(Self::Catalog, rest)?,
+            "lint" => no_arguments(Self::Lint, rest)?,
+            "audit" => no_arguments(Self::Audit, rest)?,
+            "handoffs" => no_arguments(Self::Handoffs, rest)?,
+            "show" => Self::Show(browse::ShowArgs::parse_from(rest)?),
+            "search" => Self::Search(browse::SearchArgs::parse_from(rest)?),
+            "compose" => Self::Compose(browse::ComposeArgs::parse_from(rest)?),
+            "render" => Self::Render(pages::RenderArgs::parse_from(rest)?),
+            "pages" => {
+                let mut rest = rest.into_iter();
+                match rest.next().as_deref() {
+                    Some("build") => Self::PagesBuild(pages::PagesArgs::parse_from(rest)?),
+                    Some("validate") => Self::PagesValidate(pages::PagesArgs::parse_from(rest)?),
+                    Some(other) => bail!("unknown pages command: {other}"),
+                    None => bail!("pages requires `build` or `validate`"),
+                }
+            }
+            "serve" => Self::Serve(serve::ServeArgs::parse_from(rest)?),
+            "watch" => Self::Watch(watch::WatchArgs::parse_from(rest)?),
+            _ if name.starts_with('-') => bail!("unknown argument: {name}"),
+            _ => bail!("unknown command: {name}"),
+        };
+        Ok(command)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Help => "help",
+            Self::Catalog => "catalog",
+            Self::Lint => "lint",
+            Self::Audit => "audit",
+            Self::Handoffs => "handoffs",
+            Self::Show(_) => "show",
+            Self::Search(_) => "search",
+            Self::Compose(_) => "compose",
+            Self::Render(_) => "render",
+            Self::PagesBuild(_) => "pages build",
+            Self::PagesValidate(_) => "pages validate",
+            Self::Serve(_) => "serve",
+            Self::Watch(_) => "watch",
+        }
+    }
+
+    /// Whether `--check` means something to this command.
+    fn supports_check(&self) -> bool {
+        matches!(
+            self,
+            Self::Catalog | Self::Lint | Self::Audit | Self::Handoffs
+        )
+    }
+}
+
+fn no_arguments(command: Command, rest: Vec<String>) -> Result<Command> {
+    match rest.first() {
+        Some(arg) => bail!("unknown argument: {arg}"),
+        None => Ok(command),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Invocation {
+    global: GlobalArgs,
+    command: Command,
+}
+
+impl Invocation {
+    /// Global options are picked out of every position up to a `--`; the arguments
+    /// after it go to the command untouched, so a command value such as
+    /// `--output --check` is not mistaken for a global option.
+    fn parse_from<I>(args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut global = GlobalArgs::default();
+        let mut rest = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                rest.extend(args.by_ref());
+            } else if !global.parse_flag(&arg, &mut args)? {
+                rest.push(arg);
+            }
+        }
+        let command = Command::parse_from(rest)?;
+        if global.check && !command.supports_check() {
+            bail!("`{}` does not support --check", command.name());
+        }
+        Ok(Self { global, command })
+    }
+
+    fn run(self, out: &mut dyn Write, err: &mut dyn Write) -> Result<()> {
+        let mut output = Output {
+            format: self.global.format,
+            message_format: self.global.message_format,
+            quiet: self.global.quiet,
+            out,
+            err,
+        };
+        if self.command == Command::Help {
+            return output.text(USAGE);
+        }
+        let repo_root = match &self.global.repo_root {
+            Some(path) => path.clone(),
+            None => env::current_dir().context("determine repository root")?,
+        };
+        let config = self.global.load_config(&repo_root)?;
+        let mut session = Session {
+            repo_root,
+            config,
+            check: self.global.check,
+            output,
+        };
+        match &self.command {
+            Command::Help => Ok(()),
+            Command::Catalog => catalog::run_catalog(&mut session),
+            Command::Lint => catalog::run_lint(&mut session),
+            Command::Audit => reports::run_audit(&mut session),
+            Command::Handoffs => reports::run_handoffs(&mut session),
+            Command::Show(args) => browse::run_show(&mut session, args),
+            Command::Search(args) => browse::run_search(&mut session, args),
+            Command::Compose(args) => browse::run_compose(&mut session, args),
+            Command::Render(args) => pages::run_render(&mut session, args),
+            Command::PagesBuild(args) => pages::run_pages_build(&mut session, args),
+            Command::PagesValidate(args) => pages::run_pages_validate(&mut session, args),
+            Command::Serve(args) => serve::run_serve(&mut session, args),
+            Command::Watch(args) => watch::run_watch(&mut session, args),
+        }
+    }
+}
+
+/// Where a command writes: results to `out` in the chosen format, everything else to `err`.
+struct Output<'a> {
+    format: Format,
+    message_format: MessageFormat,
+    quiet: bool,
+    out: &'a mut dyn Write,
+    err: &'a mut dyn Write,
+}
+
+impl Output<'_> {
+    /// Prints a progress message such as `wrote …` unless `--quiet` is set.
+    fn status(&mut self, message: impl fmt::Display) -> Result<()> {
+        if !self.quiet {
+            writeln!(self.err, "{message}").context("write output")?;
+        }
+        Ok(())
+    }
+
+    /// Prints a text result; ignored with `--format json`.
+    fn text(&mut self, text: &str) -> Result<()> {
+        if self.format == Format::Text {
+            self.out
+                .write_all(text.as_bytes())
+                .context("write output")?;
+        }
+        Ok(())
+    }
+
+    /// Prints a JSON result; ignored with `--format text`.
+    fn json(&mut self, value: &impl Serialize) -> Result<()> {
+        if self.format == Format::Json {
+            serde_json::to_writer_pretty(&mut *self.out, value).context("write output")?;
+            writeln!(self.out).context("write output")?;
+        }
+        Ok(())
+    }
+
+    /// Prints an explanation such as a diff to `err` in text mode; JSON results carry it instead.
+    fn details(&mut self, details: impl fmt::Display) -> Result<()> {
+        if self.format == Format::Text {
+            writeln!(self.err, "{details}").context("write output")?;
+        }
+        Ok(())
+    }
+
+    /// Prints diagnostics in text mode; JSON results carry them instead.
+    fn diagnostics(&mut self, diagnostics: &[Diagnostic]) -> Result<()> {
+        if self.format == Format::Text {
+            for diagnostic in diagnostics {
+                match self.message_format {
+                    MessageFormat::Human => writeln!(self.err, "{}", diagnostic.render_human()),
+                    MessageFormat::Machine => writeln!(self.err, "{}", diagnostic.render_machine()),
+                }
+                .context("write diagnostics")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How text diagnostics are rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum MessageFormat {
+    #[default]
+    Human,
+    Machine,
+}
+
+impl MessageFormat {
+    fn parse(value: &str) -> Result<Self> {
+        match value {
+            "human" => Ok(Self::Human),
+            "machine" => Ok(Self::Machine),
+            _ => bail!("unknown message format: {value} (expected `human` or `machine`)"),
+        }
+    }
+}
+
+/// Everything a command runs against.
+struct Session<'a> {
+    repo_root: PathBuf,
+    config: RepoConfig,
+    check: bool,
+    output: Output<'a>,
+}
+
+/// Lists the files a generator wrote or found stale.
+#[derive(Debug, Default, Serialize)]
+struct Outputs {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    written: Vec<PathBuf>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stale: Vec<PathBuf>,
+}
+
+impl Session<'_> {
+    /// Collects the personas from every configured root, stopping at the first error.
+    fn persona_entries(&self) -> Result<Vec<PersonaEntry>> {
+        let sources = crate::collect::<crate::PersonaSource>(
+            &self.config.persona_roots,
+            &self.config.catalog_options(),
+            None,
+        )
+        .into_result()
+        .with_context(|| {
+            format!(
+                "collect personas from {}",
+                display(self.config.personas_dir())
+            )
+        })?;
+        Ok(sources.into_iter().map(|source| source.entry).collect())
+    }
+
+    /// Writes each generated file, or with `--check` fails when any differs from disk.
+    fn write_outputs(
+        &mut self,
+        command: &'static str,
+        outputs: Vec<(PathBuf, String)>,
+    ) -> Result<()> {
+        let mut report = Outputs::default();
+        for (path, expected) in outputs {
+            if self.check {
+                let current = match fs::read_to_string(&path) {
+                    Ok(current) => Some(current),
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+                    Err(err) => {
+                        return Err(err).with_context(|| format!("read {}", display(&path)));
+                    }
+                };
+                let fresh = current.is_some_and(|current| {
+                    normalize_line_endings(&current) == normalize_line_endings(&expected)
+                });
+                if !fresh {
+                    report.stale.push(path);
+                }
+            } else {
+                fs::write(&path, expected).with_context(|| format!("write {}", display(&path)))?;
+                self.output
+                    .status(format_args!("wrote {}", display(&path)))?;
+                report.written.push(path);
+            }
+        }
+        self.output.json(&report)?;
+        if !report.stale.is_empty() {
+            return Err(StaleOutputs {
+                command,
+                paths: report.stale,
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+fn require_dir(path: &Path, what: &str) -> Result<()> {
+    if !path.is_dir() {
+        bail!("{what} missing: {}", display(path));
+    }
+    Ok(())
+}
+
+fn display(path: &Path) -> String {
+    path.display().to_string()
+}
+
+fn display_all(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| display(path))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn normalize_line_endings(input: &str) -> String {
+    input.replace("\r\n", "\n")
+}
+
+/// Runs the CLI against `repo_root`, returning the exit code, stdout, and stderr.
+#[cfg(test)]
+fn run_in(repo_root: &Path, args: &[&str]) -> (i32, String, String) {
+    let args = ["--repo-root", &repo_root.display().to_string()]
+        .into_iter()
+        .chain(args.iter().copied())
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let (mut out, mut err) = (Vec::new(), Vec::new());
+    let code = run(args, &mut out, &mut err);
+    let text = |bytes| String::from_utf8(bytes).expect("utf8");
+    (code, text(out), text(err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn parse(args: &[&str]) -> Result<Invocation> {
+        Invocation::parse_from(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn accepts_global_options_around_the_command() {
+        let invocation = parse(&[
+            "--quiet",
+            "catalog",
+            "--check",
+            "--format=json",
+            "--persona-root",
+            "private",
+            "--sort",
+            "name",
+        ])
+        .expect("args");
+        assert!(invocation.global.quiet && invocation.global.check);
+        assert_eq!(invocation.global.format, Format::Json);
+        assert_eq!(
+            invocation.global.persona_overlays,
+            vec![PathBuf::from("private")]
+        );
+        assert_eq!(invocation.global.sort, Some(SortOrder::Name));
+        assert_eq!(invocation.command, Command::Catalog);
+
+        let invocation = parse(&["pages", "validate", "--output", "site"]).expect("args");
+        assert!(matches!(invocation.command, Command::PagesValidate(_)));
+        let invocation = parse(&["serve", "--live", "--quiet"]).expect("args");
+        assert!(matches!(invocation.command, Command::Serve(_)));
+        assert!(parse(&["serve", "--check"]).is_err());
+        let invocation = parse(&["watch", "--interval", "250"]).expect("args");
+        assert!(matches!(invocation.command, Command::Watch(_)));
+    }
+
+    #[test]
+    fn passes_arguments_after_a_double_dash_to_the_command() {
+        let invocation = parse(&[
+            "--quiet",
+            "compose",
+            "--",
+            "--persona",
+            "one",
+            "--output",
+            "--check",
+        ])
+        .expect("args");
+        assert!(invocation.global.quiet && !invocation.global.check);
+        assert_eq!(
+            invocation.command,
+            Command::Compose(
+                browse::ComposeArgs::parse_from(
+                    ["--persona", "one", "--output", "--check"]
+                        .map(String::from)
+                        .to_vec()
+                )
+                .expect("compose args")
+            )
+        );
+        assert!(parse(&["catalog", "--", "--check"]).is_err());
+    }
+
+    #[test]
+    fn parses_settings_and_message_format() {
+        let invocation = parse(&[
+            "lint",
+            "--strict",
+            "--namespace-ids",
+            "--message-format",
+            "machine",
+        ])
+        .expect("args");
+        assert!(invocation.global.strict && invocation.global.namespace_ids);
+        assert_eq!(invocation.global.message_format, MessageFormat::Machine);
+        let invocation = parse(&["--message-format=human", "lint"]).expect("args");
+        assert_eq!(invocation.global.message_format, MessageFormat::Human);
+        assert!(parse(&["lint", "--message-format=json"]).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_usage_with_exit_code_two() {
+        let tmp = tempdir().expect("tempdir");
+        for args in [
+            &[][..],
+            &["publish"],
+            &["catalog", "--unknown"],
+            &["audit", "extra"],
+            &["show", "one", "--check"],
+            &["pages"],
+            &["lint", "--format", "yaml"],
+            &["catalog", "--sort", "date"],
+        ] {
+            let (code, out, err) = run_in(tmp.path(), args);
+            assert_eq!(code, EXIT_USAGE, "{args:?}: {err}");
+            assert!(out.is_empty());
+            assert!(err.starts_with("error: "), "{err}");
+        }
+        let (_, _, err) = run_in(tmp.path(), &["show", "one", "--check"]);
+        assert!(err.contains("`show` does not support --check"), "{err}");
+    }
+
+    #[test]
+    fn help_prints_usage() {
+        let tmp = tempdir().expect("tempdir");
+        let (code, out, _) = run_in(tmp.path(), &["--help"]);
+        assert_eq!(code, EXIT_SUCCESS);
+        assert!(out.starts_with("usage: codex-tools"));
+    }
+}
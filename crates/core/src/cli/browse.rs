@@ -0,0 +1,383 @@
+use super::{Session, display};
+use crate::CatalogError;
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ShowArgs {
+    id: String,
+}
+
+impl ShowArgs {
+    pub(super) fn parse_from(args: Vec<String>) -> Result<Self> {
+        let mut args = args.into_iter();
+        let Some(id) = args.next().filter(|arg| !arg.starts_with('-')) else {
+            bail!("show requires an id");
+        };
+        if let Some(arg) = args.next() {
+            bail!("unknown argument: {arg}");
+        }
+        Ok(Self { id })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct SearchArgs {
+    terms: Vec<String>,
+}
+
+impl SearchArgs {
+    pub(super) fn parse_from(args: Vec<String>) -> Result<Self> {
+        if let Some(arg) = args.iter().find(|arg| arg.starts_with('-')) {
+            bail!("unknown argument: {arg}");
+        }
+        if args.is_empty() {
+            bail!("search requires at least one term");
+        }
+        Ok(Self {
+            terms: args.iter().map(|term| term.to_lowercase()).collect(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ComposeArgs {
+    persona: String,
+    scenario: Option<String>,
+    output: Option<PathBuf>,
+}
+
+impl ComposeArgs {
+    pub(super) fn parse_from(args: Vec<String>) -> Result<Self> {
+        let mut persona = None;
+        let mut scenario = None;
+        let mut output = None;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = |flag: &str| {
+                args.next()
+                    .with_context(|| format!("{flag} requires a value"))
+            };
+            match arg.as_str() {
+                "--persona" => persona = Some(value("--persona")?),
+                "--scenario" => scenario = Some(value("--scenario")?),
+                "--output" => output = Some(PathBuf::from(value("--output")?)),
+                _ => bail!("unknown argument: {arg}"),
+            }
+        }
+        let Some(persona) = persona else {
+            bail!("--persona <id> is required");
+        };
+        Ok(Self {
+            persona,
+            scenario,
+            output,
+        })
+    }
+}
+
+/// Prints a persona (or, failing that, a scenario) with its templates expanded.
+pub(super) fn run_show(session: &mut Session, args: &ShowArgs) -> Result<()> {
+    let config = &session.config;
+    let options = config.catalog_options();
+    let personas = crate::collect::<crate::PersonaSource>(&config.persona_roots, &options, None)
+        .into_result()
+        .with_context(|| format!("collect personas from {}", display(config.personas_dir())))?;
+    if let Some(persona) = personas
+        .iter()
+        .find(|source| source.entry.meta.id == args.id)
+    {
+        return show(
+            session,
+            "persona",
+            &persona.path,
+            &persona.entry,
+            &persona.body,
+            persona.published_markdown(),
+        );
+    }
+    let scenarios = crate::collect::<crate::ScenarioSource>(&config.scenario_roots, &options, None)
+        .into_result()
+        .with_context(|| format!("collect scenarios from {}", display(config.scenarios_dir())))?;
+    if let Some(scenario) = scenarios
+        .iter()
+        .find(|source| source.entry.meta.id == args.id)
+    {
+        return show(
+            session,
+            "scenario",
+            &scenario.path,
+            &scenario.entry,
+            &scenario.body,
+            scenario.published_markdown(),
+        );
+    }
+    bail!("no persona or scenario with id `{}`", args.id)
+}
+
+fn show<E: Serialize>(
+    session: &mut Session,
+    kind: &'static str,
+    path: &Path,
+    entry: &E,
+    body: &str,
+    markdown: Result<String, CatalogError>,
+) -> Result<()> {
+    #[derive(Serialize)]
+    struct Shown<'a, E> {
+        kind: &'static str,
+        path: &'a Path,
+        #[serde(flatten)]
+        entry: &'a E,
+        body: &'a str,
+    }
+
+    let markdown = markdown.with_context(|| format!("render {}", display(path)))?;
+    session.output.text(&markdown)?;
+    session.output.json(&Shown {
+        kind,
+        path,
+        entry,
+        body,
+    })
+}
+
+/// One `search` result.
+#[derive(Debug, Serialize)]
+struct SearchHit<'a> {
+    kind: &'static str,
+    id: &'a str,
+    name: &'a str,
+    description: Option<&'a str>,
+    tags: &'a [String],
+    uri: &'a str,
+    path: &'a Path,
+}
+
+impl SearchHit<'_> {
+    fn matches(&self, terms: &[String]) -> bool {
+        let mut haystack = vec![self.id, self.name];
+        haystack.extend(self.description);
+        haystack.extend(self.tags.iter().map(String::as_str));
+        let haystack = haystack.join("\n").to_lowercase();
+        terms.iter().all(|term| haystack.contains(term))
+    }
+}
+
+/// Lists the personas and scenarios whose id, name, description, or tags contain every term.
+pub(super) fn run_search(session: &mut Session, args: &SearchArgs) -> Result<()> {
+    let config = &session.config;
+    let options = config.catalog_options();
+    let personas = crate::collect::<crate::PersonaSource>(&config.persona_roots, &options, None)
+        .into_result()
+        .with_context(|| format!("collect personas from {}", display(config.personas_dir())))?;
+    let scenarios = crate::collect::<crate::ScenarioSource>(&config.scenario_roots, &options, None)
+        .into_result()
+        .with_context(|| format!("collect scenarios from {}", display(config.scenarios_dir())))?;
+
+    let personas = personas.iter().map(|source| SearchHit {
+        kind: "persona",
+        id: &source.entry.meta.id,
+        name: &source.entry.meta.name,
+        description: source.entry.meta.description.as_deref(),
+        tags: source.entry.meta.tags.as_deref().unwrap_or_default(),
+        uri: &source.entry.uri,
+        path: &source.path,
+    });
+    let scenarios = scenarios.iter().map(|source| SearchHit {
+        kind: "scenario",
+        id: &source.entry.meta.id,
+        name: &source.entry.meta.name,
+        description: source.entry.meta.description.as_deref(),
+        tags: source.entry.meta.tags.as_deref().unwrap_or_default(),
+        uri: &source.entry.uri,
+        path: &source.path,
+    });
+    let hits = personas
+        .chain(scenarios)
+        .filter(|hit| hit.matches(&args.terms))
+        .collect::<Vec<_>>();
+
+    if hits.is_empty() {
+        session.output.status(format_args!(
+            "no personas or scenarios match `{}`",
+            args.terms.join(" ")
+        ))?;
+    }
+    let mut text = String::new();
+    for hit in &hits {
+        text.push_str(&format!("{:<8} {}: {}", hit.kind, hit.id, hit.name));
+        if let Some(description) = hit.description {
+            text.push_str(&format!(" — {description}"));
+        }
+        text.push('\n');
+    }
+    session.output.text(&text)?;
+    session.output.json(&hits)
+}
+
+/// Composes `AGENTS.md`, a persona, and optionally a scenario into one prompt.
+pub(super) fn run_compose(session: &mut Session, args: &ComposeArgs) -> Result<()> {
+    let prompt = crate::compose_with(
+        &session.repo_root,
+        &session.config,
+        &args.persona,
+        args.scenario.as_deref(),
+    )
+    .with_context(|| format!("compose prompt for persona `{}`", args.persona))?;
+
+    #[derive(Serialize)]
+    struct Composed<'a> {
+        persona: &'a str,
+        scenario: Option<&'a str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        written: Option<&'a Path>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        prompt: Option<&'a str>,
+    }
+    let mut composed = Composed {
+        persona: &args.persona,
+        scenario: args.scenario.as_deref(),
+        written: None,
+        prompt: None,
+    };
+    match &args.output {
+        Some(path) => {
+            fs::write(path, &prompt).with_context(|| format!("write {}", display(path)))?;
+            session
+                .output
+                .status(format_args!("wrote {}", display(path)))?;
+            composed.written = Some(path);
+        }
+        None => {
+            session.output.text(&prompt)?;
+            composed.prompt = Some(&prompt);
+        }
+    }
+    session.output.json(&composed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{EXIT_FAILURE, EXIT_SUCCESS, EXIT_USAGE, run_in};
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write_repo(root: &Path) {
+        fs::create_dir(root.join("personas")).expect("personas dir");
+        fs::create_dir(root.join("scenarios")).expect("scenarios dir");
+        fs::create_dir(root.join("snippets")).expect("snippets dir");
+        fs::write(root.join("AGENTS.md"), "# Base\n").expect("agents");
+        fs::write(
+            root.join("snippets").join("sign.md"),
+            "Signed **{{ id }}**.\n",
+        )
+        .expect("snippet");
+        fs::write(
+            root.join("personas").join("ONE.md"),
+            "---\nid: one\nname: One\ndescription: Ships Rust crates\ntags: [rust]\nversion: 0.2\n---\n# One\n\n{{> snippets/sign.md}}\n",
+        )
+        .expect("persona");
+        fs::write(
+            root.join("scenarios").join("AUDIT.md"),
+            "---\nid: audit\nname: Audit\ntags: [security]\n---\n# Audit\n",
+        )
+        .expect("scenario");
+    }
+
+    #[test]
+    fn show_prints_expanded_markdown_or_entry() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(tmp.path());
+
+        let (code, out, err) = run_in(tmp.path(), &["show", "one"]);
+        assert_eq!(code, EXIT_SUCCESS, "{err}");
+        assert!(out.starts_with("---\nid: one\n"), "{out}");
+        assert!(out.ends_with("# One\n\nSigned **one**.\n"), "{out}");
+
+        let (code, out, _) = run_in(tmp.path(), &["show", "audit", "--format", "json"]);
+        assert_eq!(code, EXIT_SUCCESS);
+        let shown: serde_json::Value = serde_json::from_str(&out).expect("json");
+        assert_eq!(shown["kind"], "scenario");
+        assert_eq!(shown["id"], "audit");
+        assert_eq!(shown["body"], "# Audit\n");
+
+        let (code, _, err) = run_in(tmp.path(), &["show", "nope"]);
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(
+            err.contains("no persona or scenario with id `nope`"),
+            "{err}"
+        );
+        assert_eq!(run_in(tmp.path(), &["show"]).0, EXIT_USAGE);
+    }
+
+    #[test]
+    fn search_matches_every_term_case_insensitively() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(tmp.path());
+
+        let (code, out, _) = run_in(tmp.path(), &["search", "RUST", "ships"]);
+        assert_eq!(code, EXIT_SUCCESS);
+        assert_eq!(out, "persona  one: One — Ships Rust crates\n");
+
+        let (_, out, _) = run_in(tmp.path(), &["search", "security", "--format", "json"]);
+        let results: serde_json::Value = serde_json::from_str(&out).expect("json");
+        assert_eq!(results.as_array().map(Vec::len), Some(1));
+        assert_eq!(results[0]["kind"], "scenario");
+
+        let (code, out, err) = run_in(tmp.path(), &["search", "python"]);
+        assert_eq!(code, EXIT_SUCCESS);
+        assert!(out.is_empty());
+        assert!(err.contains("no personas or scenarios match `python`"));
+    }
+
+    #[test]
+    fn compose_prints_prompt() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(tmp.path());
+
+        let (code, out, err) = run_in(
+            tmp.path(),
+            &["compose", "--persona", "one", "--scenario", "audit"],
+        );
+        assert_eq!(code, EXIT_SUCCESS, "{err}");
+        assert!(out.starts_with("<!--\nComposed prompt."));
+        assert!(out.contains("- personas/ONE.md (id: one, version: 0.2)\n"));
+        assert!(out.ends_with("# Base\n\n# One\n\nSigned **one**.\n\n# Audit\n"));
+    }
+
+    #[test]
+    fn compose_writes_output_file() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(tmp.path());
+        let output = tmp.path().join("prompt.md");
+
+        let (code, out, _) = run_in(
+            tmp.path(),
+            &["compose", "--persona", "one", "--output", &display(&output)],
+        );
+        assert_eq!(code, EXIT_SUCCESS);
+        assert!(out.is_empty());
+        let prompt = fs::read_to_string(output).expect("prompt");
+        assert!(prompt.ends_with("# Base\n\n# One\n\nSigned **one**.\n"));
+    }
+
+    #[test]
+    fn compose_reports_unknown_persona() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(tmp.path());
+        let (code, _, err) = run_in(tmp.path(), &["compose", "--persona", "two"]);
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(err.contains("no persona with id `two`"));
+    }
+
+    #[test]
+    fn compose_args_require_persona() {
+        let err = ComposeArgs::parse_from(Vec::new()).unwrap_err();
+        assert!(err.to_string().contains("--persona <id> is required"));
+        let err = ComposeArgs::parse_from(vec!["--scenario".into()]).unwrap_err();
+        assert!(err.to_string().contains("--scenario requires a value"));
+    }
+}
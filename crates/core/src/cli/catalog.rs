@@ -0,0 +1,545 @@
+use super::{Session, StaleOutputs, display, require_dir};
+use crate::{
+    CatalogDrift, CatalogOptions, CatalogReport, Diagnostic, PersonaSource, ScenarioSource,
+};
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// The JSON result of `catalog` and `lint`.
+#[derive(Debug, Serialize)]
+struct CatalogSummary {
+    personas: usize,
+    scenarios: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    written: Vec<PathBuf>,
+    /// Catalogs that differ from their sources, with `--check`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stale: Vec<CatalogDrift>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// What to do once the sources validate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Write,
+    /// Compare the checked-in catalogs with the generated ones (`catalog --check`).
+    Compare,
+    /// Only validate the sources (`lint`).
+    Validate,
+}
+
+/// Regenerates both catalogs, or with `--check` validates the sources like `lint` and
+/// fails when a checked-in catalog differs from them.
+pub(super) fn run_catalog(session: &mut Session) -> Result<()> {
+    let mode = if session.check {
+        Mode::Compare
+    } else {
+        Mode::Write
+    };
+    collect(session, mode)
+}
+
+/// Validates persona and scenario sources, including their body structure, without writing.
+pub(super) fn run_lint(session: &mut Session) -> Result<()> {
+    collect(session, Mode::Validate)
+}
+
+fn collect(session: &mut Session, mode: Mode) -> Result<()> {
+    let config = &session.config;
+    require_dir(config.personas_dir(), "personas directory")?;
+    if !config.agents_path.is_file() {
+        bail!("AGENTS.md missing: {}", display(&config.agents_path));
+    }
+    require_dir(config.scenarios_dir(), "scenarios directory")?;
+    let overlays = config.persona_roots[1..]
+        .iter()
+        .chain(&config.scenario_roots[1..]);
+    for overlay in overlays {
+        require_dir(overlay, "overlay directory")?;
+    }
+
+    let options = CatalogOptions {
+        structure: mode != Mode::Write,
+        ..config.catalog_options()
+    };
+    let personas = crate::collect::<PersonaSource>(&config.persona_roots, &options, None);
+    let scenarios = crate::collect::<ScenarioSource>(&config.scenario_roots, &options, None);
+
+    let mut summary = CatalogSummary {
+        personas: personas.sources.len(),
+        scenarios: scenarios.sources.len(),
+        written: Vec::new(),
+        stale: Vec::new(),
+        diagnostics: diagnostics(&personas)
+            .chain(diagnostics(&scenarios))
+            .collect(),
+    };
+    session.output.diagnostics(&summary.diagnostics)?;
+    let errors = personas.errors.len() + scenarios.errors.len();
+    if errors > 0 {
+        session.output.json(&summary)?;
+        bail!("found {errors} error(s) in persona and scenario sources");
+    }
+
+    let config = &session.config;
+    let personas = personas
+        .sources
+        .into_iter()
+        .map(|source| source.entry)
+        .collect();
+    let scenarios = scenarios
+        .sources
+        .into_iter()
+        .map(|source| source.entry)
+        .collect();
+    if mode != Mode::Write {
+        if mode == Mode::Compare {
+            let index = crate::build_index(
+                config.personas_dir(),
+                &config.agents_path,
+                personas,
+                &options,
+            )
+            .with_context(|| format!("generate catalog for {}", display(config.personas_dir())))?;
+            summary.stale.extend(
+                crate::catalog_drift(config.personas_dir(), &index).with_context(|| {
+                    format!("check catalog for {}", display(config.personas_dir()))
+                })?,
+            );
+            let index = crate::build_scenario_index(
+                config.scenarios_dir(),
+                &config.agents_path,
+                scenarios,
+                &options,
+            )
+            .with_context(|| format!("generate catalog for {}", display(config.scenarios_dir())))?;
+            summary.stale.extend(
+                crate::catalog_drift(config.scenarios_dir(), &index).with_context(|| {
+                    format!("check catalog for {}", display(config.scenarios_dir()))
+                })?,
+            );
+        }
+        session.output.status(format_args!(
+            "checked {} personas in {}",
+            summary.personas,
+            display(config.personas_dir())
+        ))?;
+        session.output.status(format_args!(
+            "checked {} scenarios in {}",
+            summary.scenarios,
+            display(config.scenarios_dir())
+        ))?;
+        for drift in &summary.stale {
+            session.output.details(drift)?;
+        }
+        session.output.json(&summary)?;
+        if !summary.stale.is_empty() {
+            let paths = summary.stale.into_iter().map(|drift| drift.path).collect();
+            return Err(StaleOutputs {
+                command: "catalog",
+                paths,
+            }
+            .into());
+        }
+        return Ok(());
+    }
+
+    crate::write_index(
+        config.personas_dir(),
+        &config.agents_path,
+        personas,
+        &options,
+    )
+    .with_context(|| format!("generate catalog for {}", display(config.personas_dir())))?;
+    summary
+        .written
+        .push(config.personas_dir().join("catalog.json"));
+
+    crate::write_scenario_index(
+        config.scenarios_dir(),
+        &config.agents_path,
+        scenarios,
+        &options,
+    )
+    .with_context(|| format!("generate catalog for {}", display(config.scenarios_dir())))?;
+    summary
+        .written
+        .push(config.scenarios_dir().join("catalog.json"));
+
+    for path in &summary.written {
+        session
+            .output
+            .status(format_args!("wrote {}", display(path)))?;
+    }
+    session.output.json(&summary)
+}
+
+/// Every warning and error from `report`, warnings first.
+pub(super) fn diagnostics<S>(report: &CatalogReport<S>) -> impl Iterator<Item = Diagnostic> + '_ {
+    let warnings = report.warnings.iter().map(|warning| warning.diagnostic());
+    let errors = report.errors.iter().flat_map(|err| err.diagnostics());
+    warnings.chain(errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{EXIT_FAILURE, EXIT_STALE, EXIT_SUCCESS, run_in};
+    use super::*;
+    use crate::CONFIG_FILE;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    #[test]
+    fn run_in_repo_writes_catalog() {
+        let tmp = tempdir().expect("tempdir");
+        let repo_root = tmp.path();
+        let personas_dir = repo_root.join("personas");
+        let scenarios_dir = repo_root.join("scenarios");
+        fs::create_dir(&personas_dir).expect("personas dir");
+        fs::create_dir(&scenarios_dir).expect("scenarios dir");
+        fs::write(repo_root.join("AGENTS.md"), "# Test\n").expect("agents");
+        fs::write(
+            personas_dir.join("ONE.md"),
+            "---\nid: one\nname: One\n---\nbody\n",
+        )
+        .expect("persona");
+        fs::write(
+            scenarios_dir.join("AUDIT.md"),
+            "---\nid: audit\nname: Audit\n---\nbody\n",
+        )
+        .expect("scenario");
+
+        let (code, out, err) = run_in(repo_root, &["catalog"]);
+        assert_eq!(code, EXIT_SUCCESS, "{err}");
+        assert!(out.is_empty());
+        assert!(err.contains("wrote "), "{err}");
+
+        let catalog = personas_dir.join("catalog.json");
+        let contents = fs::read_to_string(catalog).expect("catalog contents");
+        assert!(contents.contains("\"id\": \"one\""));
+
+        let scenario_catalog = scenarios_dir.join("catalog.json");
+        let contents = fs::read_to_string(scenario_catalog).expect("scenario catalog contents");
+        assert!(contents.contains("\"id\": \"audit\""));
+    }
+
+    #[test]
+    fn missing_personas_dir_returns_error() {
+        let tmp = tempdir().expect("tempdir");
+        fs::write(tmp.path().join("AGENTS.md"), "# Test\n").expect("agents");
+        let (code, _, err) = run_in(tmp.path(), &["catalog"]);
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(err.contains("personas directory missing"));
+    }
+
+    #[test]
+    fn missing_agents_file_returns_error() {
+        let tmp = tempdir().expect("tempdir");
+        fs::create_dir(tmp.path().join("personas")).expect("personas");
+        let (_, _, err) = run_in(tmp.path(), &["catalog"]);
+        assert!(err.contains("AGENTS.md missing"));
+    }
+
+    #[test]
+    fn missing_scenarios_dir_returns_error() {
+        let tmp = tempdir().expect("tempdir");
+        fs::create_dir(tmp.path().join("personas")).expect("personas");
+        fs::write(tmp.path().join("AGENTS.md"), "# Test\n").expect("agents");
+        let (_, _, err) = run_in(tmp.path(), &["catalog"]);
+        assert!(err.contains("scenarios directory missing"));
+    }
+
+    fn write_repo(root: &Path, persona: &str) {
+        fs::create_dir(root.join("personas")).expect("personas dir");
+        fs::create_dir(root.join("scenarios")).expect("scenarios dir");
+        fs::write(root.join("AGENTS.md"), "# Test\n").expect("agents");
+        fs::write(root.join("personas").join("ONE.md"), persona).expect("persona");
+    }
+
+    #[test]
+    fn check_reports_structure_issues_without_writing() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(
+            tmp.path(),
+            "---\nid: one\nname: One\n---\n# One\n\n## Role Snapshot\n",
+        );
+
+        for command in ["catalog", "lint"] {
+            let (code, _, err) = run_in(tmp.path(), &[command, "--check"]);
+            assert_eq!(code, EXIT_FAILURE);
+            assert!(err.contains("found 1 error(s)"), "{err}");
+            assert!(err.contains("missing required section `## Responsibilities Checklist`"));
+            assert!(!tmp.path().join("personas").join("catalog.json").exists());
+        }
+    }
+
+    #[test]
+    fn check_accepts_valid_personas() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(
+            tmp.path(),
+            "---\nid: one\nname: One\n---\n# One\n\n## Role Snapshot\n\n## Responsibilities Checklist\n\n## When to Switch Away\n\n## Required Artifacts\n\n## Collaboration Signals\n",
+        );
+
+        let (code, _, err) = run_in(tmp.path(), &["lint"]);
+        assert_eq!(code, EXIT_SUCCESS, "{err}");
+        assert!(err.contains("checked 1 personas in "), "{err}");
+        let (code, _, err) = run_in(tmp.path(), &["lint", "--quiet"]);
+        assert_eq!(code, EXIT_SUCCESS);
+        assert!(!err.contains("checked"), "{err}");
+    }
+
+    #[test]
+    fn check_reports_catalog_drift_with_its_own_exit_code() {
+        let tmp = tempdir().expect("tempdir");
+        let persona = "---\nid: one\nname: One\n---\n# One\n\n## Role Snapshot\n\n## Responsibilities Checklist\n\n## When to Switch Away\n\n## Required Artifacts\n\n## Collaboration Signals\n";
+        write_repo(tmp.path(), persona);
+        let catalog = tmp.path().join("personas").join("catalog.json");
+
+        let (code, _, err) = run_in(tmp.path(), &["catalog", "--check"]);
+        assert_eq!(code, EXIT_STALE);
+        assert!(err.contains("catalog.json is missing"), "{err}");
+        assert!(!catalog.exists());
+
+        assert_eq!(run_in(tmp.path(), &["catalog"]).0, EXIT_SUCCESS);
+        let contents = fs::read_to_string(&catalog).expect("catalog");
+        fs::write(&catalog, contents.replace('\n', "\r\n")).expect("crlf catalog");
+        let (code, _, err) = run_in(tmp.path(), &["catalog", "--check"]);
+        assert_eq!(code, EXIT_SUCCESS, "{err}");
+
+        fs::write(
+            tmp.path().join("personas").join("ONE.md"),
+            persona.replace("One", "Uno"),
+        )
+        .expect("persona");
+        let (code, _, err) = run_in(tmp.path(), &["catalog", "--check"]);
+        assert_eq!(code, EXIT_STALE);
+        assert!(
+            err.contains("~ personas[id=one].name: \"One\" → \"Uno\""),
+            "{err}"
+        );
+        assert!(err.contains("catalog.json out of date"), "{err}");
+        assert!(!err.contains("scenarios/catalog.json"), "{err}");
+
+        let (code, out, _) = run_in(tmp.path(), &["catalog", "--check", "--format", "json"]);
+        assert_eq!(code, EXIT_STALE);
+        let report: serde_json::Value = serde_json::from_str(&out).expect("json");
+        assert_eq!(report["stale"][0]["status"], "changed");
+        assert_eq!(report["stale"][0]["changes"][0]["new"], "Uno");
+        assert!(
+            fs::read_to_string(&catalog)
+                .expect("catalog")
+                .contains("\"One\"")
+        );
+    }
+
+    #[test]
+    fn check_reports_empty_scenario_prompt_template() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(
+            tmp.path(),
+            "---\nid: one\nname: One\n---\n# One\n\n## Role Snapshot\n\n## Responsibilities Checklist\n\n## When to Switch Away\n\n## Required Artifacts\n\n## Collaboration Signals\n",
+        );
+        fs::write(
+            tmp.path().join("scenarios").join("AUDIT.md"),
+            "---\nid: audit\nname: Audit\n---\n# Audit\n\n## Goal\ng\n\n## When to Use\nw\n\n## Inputs\ni\n\n## Execution Steps\ne\n\n## Prompt Template\n```\n```\n",
+        )
+        .expect("scenario");
+
+        let (code, _, err) = run_in(tmp.path(), &["lint"]);
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(err.contains("error: `## Prompt Template` contains no prompt text"));
+        assert!(err.contains("AUDIT.md:19:1"));
+    }
+
+    #[test]
+    fn reports_every_broken_persona_before_failing() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(tmp.path(), "---\nid: one\nname: One\n---\nbody\n");
+        let personas_dir = tmp.path().join("personas");
+        fs::write(personas_dir.join("TWO.md"), "missing front matter\n").expect("persona");
+        fs::write(
+            personas_dir.join("THREE.md"),
+            "---\nid: one\nname: Three\n---\n",
+        )
+        .expect("persona");
+
+        let (code, _, err) = run_in(tmp.path(), &["catalog"]);
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(err.contains("found 2 error(s)"));
+        assert!(err.contains("duplicate id `one`"));
+        assert!(err.contains("TWO.md:1:1\n"));
+        assert!(err.contains("error: front matter missing"));
+        assert!(err.contains("warning:"));
+        assert!(!personas_dir.join("catalog.json").exists());
+    }
+
+    #[test]
+    fn json_format_reports_diagnostics_on_stdout() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(tmp.path(), "---\nid: one\nname: One\n---\nbody\n");
+        fs::write(
+            tmp.path().join("personas").join("TWO.md"),
+            "no front matter\n",
+        )
+        .expect("persona");
+
+        let (code, out, err) = run_in(tmp.path(), &["catalog", "--format", "json"]);
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(err.starts_with("error: found 1 error(s)"), "{err}");
+        let summary: serde_json::Value = serde_json::from_str(&out).expect("json");
+        assert_eq!(summary["personas"], 1);
+        let severities = summary["diagnostics"]
+            .as_array()
+            .expect("diagnostics")
+            .iter()
+            .map(|diagnostic| diagnostic["severity"].as_str().expect("severity"))
+            .collect::<Vec<_>>();
+        assert!(severities.contains(&"error"), "{out}");
+        assert!(severities.contains(&"warning"), "{out}");
+    }
+
+    #[test]
+    fn strict_rejects_mistyped_front_matter_fields() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(
+            tmp.path(),
+            "---\nid: one\nname: One\ntag: [a]\nmodel_hints: [fast]\n---\nbody\n",
+        );
+
+        let (code, _, err) = run_in(tmp.path(), &["catalog"]);
+        assert_eq!(code, EXIT_SUCCESS, "lenient run: {err}");
+
+        let (code, _, err) = run_in(
+            tmp.path(),
+            &["catalog", "--strict", "--message-format", "machine"],
+        );
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(err.contains("found 1 error(s)"));
+        assert!(err.contains("ONE.md:4:1: error: unknown field `tag`; did you mean `tags`?"));
+        assert!(
+            err.contains("ONE.md:5:1: error: unknown field `model_hints`"),
+            "{err}"
+        );
+
+        fs::write(
+            tmp.path().join(CONFIG_FILE),
+            "custom_fields: [model_hints]\n",
+        )
+        .expect("config");
+        let (code, _, err) = run_in(tmp.path(), &["catalog", "--strict"]);
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(!err.contains("model_hints"), "{err}");
+    }
+
+    #[test]
+    fn persona_roots_override_earlier_ids() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(
+            tmp.path(),
+            "---\nid: one\nname: One\ndescription: Public\ntags: [a]\n---\nbody\n",
+        );
+        let private = tmp.path().join("private");
+        fs::create_dir(&private).expect("private dir");
+        fs::write(
+            private.join("ONE.md"),
+            "---\nid: one\nname: One\ndescription: Private\ntags: [a]\n---\nbody\n",
+        )
+        .expect("override");
+        fs::write(
+            private.join("TWO.md"),
+            "---\nid: two\nname: Two\ndescription: Extra\ntags: [a]\n---\nbody\n",
+        )
+        .expect("addition");
+
+        let (code, _, msg) = run_in(tmp.path(), &["catalog", "--persona-root", "private"]);
+        assert_eq!(code, EXIT_SUCCESS, "{msg}");
+        assert!(msg.contains("warning: overrides `one` from "), "{msg}");
+        assert!(msg.contains(&display(&private.join("ONE.md"))), "{msg}");
+        let index: crate::Index = serde_json::from_str(
+            &fs::read_to_string(tmp.path().join("personas").join("catalog.json")).expect("catalog"),
+        )
+        .expect("index");
+        let summary = index
+            .personas
+            .iter()
+            .map(|entry| {
+                (
+                    entry.meta.id.as_str(),
+                    entry.meta.description.as_deref(),
+                    entry.root.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("one", Some("Private"), "private"),
+                ("two", Some("Extra"), "private"),
+            ]
+        );
+
+        let (_, _, err) = run_in(tmp.path(), &["catalog", "--persona-root", "missing"]);
+        assert!(err.contains("overlay directory missing"));
+    }
+
+    #[test]
+    fn config_file_sets_roots_and_sort_order_under_cli_flags() {
+        let tmp = tempdir().expect("tempdir");
+        let root = tmp.path();
+        fs::create_dir(root.join("agents")).expect("agents dir");
+        fs::create_dir(root.join("scenarios")).expect("scenarios dir");
+        fs::write(root.join("AGENTS.md"), "# Test\n").expect("agents");
+        for (file, id, name) in [("A.md", "a", "Zed"), ("B.md", "b", "Amy")] {
+            fs::write(
+                root.join("agents").join(file),
+                format!("---\nid: {id}\nname: {name}\ndescription: d\ntags: [t]\n---\nbody\n"),
+            )
+            .expect("persona");
+        }
+        fs::write(
+            root.join("codex-tools.yaml"),
+            "personas: [agents]\nsort: name\n",
+        )
+        .expect("config");
+
+        let ids = |root: &Path| {
+            let index: crate::Index = serde_json::from_str(
+                &fs::read_to_string(root.join("agents").join("catalog.json")).expect("catalog"),
+            )
+            .expect("index");
+            index
+                .personas
+                .into_iter()
+                .map(|entry| entry.meta.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run_in(root, &["catalog"]).0, EXIT_SUCCESS);
+        assert_eq!(ids(root), vec!["b", "a"]);
+
+        assert_eq!(run_in(root, &["catalog", "--sort", "id"]).0, EXIT_SUCCESS);
+        assert_eq!(ids(root), vec!["a", "b"]);
+
+        let (code, _, err) = run_in(root, &["catalog", "--config", "other.yaml"]);
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(err.contains("other.yaml"), "{err}");
+    }
+
+    #[test]
+    fn machine_format_reports_yaml_location() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(
+            tmp.path(),
+            "---\r\nid: one\r\nname: One\r\ntags: [a\r\n---\r\nbody\r\n",
+        );
+
+        let (_, _, msg) = run_in(tmp.path(), &["--message-format=machine", "catalog"]);
+        let line = msg
+            .lines()
+            .find(|line| line.contains(": error: "))
+            .expect("error line");
+        assert!(line.contains("ONE.md:5:1: error: failed to parse YAML"));
+    }
+}
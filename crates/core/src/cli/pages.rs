@@ -0,0 +1,344 @@
+use super::{Session, display, require_dir};
+use crate::RepoConfig;
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct RenderArgs {
+    output: PathBuf,
+}
+
+impl RenderArgs {
+    pub(super) fn parse_from(args: Vec<String>) -> Result<Self> {
+        let output = parse_output(args)?;
+        let Some(output) = output else {
+            bail!("--output <dir> is required");
+        };
+        Ok(Self { output })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct PagesArgs {
+    /// The artifact directory; defaults to `public/` under the repository.
+    output: Option<PathBuf>,
+}
+
+impl PagesArgs {
+    pub(super) fn output_in(&self, repo_root: &Path) -> PathBuf {
+        match &self.output {
+            Some(output) => repo_root.join(output),
+            None => repo_root.join("public"),
+        }
+    }
+
+    pub(super) fn parse_from<I>(args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        Ok(Self {
+            output: parse_output(args)?,
+        })
+    }
+}
+
+fn parse_output<I>(args: I) -> Result<Option<PathBuf>>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut output = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => {
+                output = Some(PathBuf::from(
+                    args.next().context("--output requires a value")?,
+                ))
+            }
+            _ => bail!("unknown argument: {arg}"),
+        }
+    }
+    Ok(output)
+}
+
+#[derive(Debug, Default, Serialize)]
+struct Rendered {
+    written: Vec<PathBuf>,
+}
+
+/// Writes every persona and scenario with its templates expanded to `output/<relative path>`.
+pub(super) fn run_render(session: &mut Session, args: &RenderArgs) -> Result<()> {
+    let config = &session.config;
+    require_dir(config.personas_dir(), "personas directory")?;
+    require_dir(config.scenarios_dir(), "scenarios directory")?;
+
+    let options = config.catalog_options();
+    let personas = crate::collect::<crate::PersonaSource>(&config.persona_roots, &options, None)
+        .into_result()
+        .with_context(|| format!("collect personas from {}", display(config.personas_dir())))?;
+    let scenarios = crate::collect::<crate::ScenarioSource>(&config.scenario_roots, &options, None)
+        .into_result()
+        .with_context(|| format!("collect scenarios from {}", display(config.scenarios_dir())))?;
+
+    let sources = personas
+        .iter()
+        .map(|source| (&source.path, source.published_markdown()))
+        .chain(
+            scenarios
+                .iter()
+                .map(|source| (&source.path, source.published_markdown())),
+        );
+    let mut rendered = Rendered::default();
+    for (path, contents) in sources {
+        let relative = path.strip_prefix(&session.repo_root).unwrap_or(path);
+        let target = args.output.join(relative);
+        let contents = contents.with_context(|| format!("render {}", display(path)))?;
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("create {}", display(parent)))?;
+        }
+        fs::write(&target, contents).with_context(|| format!("write {}", display(&target)))?;
+        session
+            .output
+            .status(format_args!("wrote {}", display(&target)))?;
+        rendered.written.push(target);
+    }
+    session.output.json(&rendered)
+}
+
+/// Assembles the GitHub Pages artifact, replacing the output directory.
+pub(super) fn run_pages_build(session: &mut Session, args: &PagesArgs) -> Result<()> {
+    require_pages_sources(&session.config)?;
+    let output = args.output_in(&session.repo_root);
+    let artifact = crate::build_pages(&session.repo_root, &session.config, &output, None)
+        .with_context(|| format!("build {}", display(&output)))?;
+    session.output.status(format_args!(
+        "wrote {} files to {}",
+        artifact.files.len(),
+        display(&output)
+    ))?;
+
+    #[derive(Serialize)]
+    struct Built {
+        output: PathBuf,
+        files: Vec<PathBuf>,
+    }
+    session.output.json(&Built {
+        output,
+        files: artifact.files,
+    })
+}
+
+/// Fails early with a readable message when an input of the Pages artifact is missing.
+pub(super) fn require_pages_sources(config: &RepoConfig) -> Result<()> {
+    require_dir(config.personas_dir(), "personas directory")?;
+    if !config.agents_path.is_file() {
+        bail!("AGENTS.md missing: {}", display(&config.agents_path));
+    }
+    require_dir(config.scenarios_dir(), "scenarios directory")
+}
+
+/// Checks a built GitHub Pages artifact: required files, catalog URIs, catalog copies,
+/// and `static.json` header sources.
+pub(super) fn run_pages_validate(session: &mut Session, args: &PagesArgs) -> Result<()> {
+    let output = args.output_in(&session.repo_root);
+    let issues = crate::validate_pages(&output, &session.config.pages_base_url)
+        .with_context(|| format!("validate {}", display(&output)))?;
+    for issue in &issues {
+        session.output.details(format_args!("error: {issue}"))?;
+    }
+
+    #[derive(Serialize)]
+    struct Validated {
+        output: PathBuf,
+        issues: Vec<String>,
+    }
+    let count = issues.len();
+    session.output.json(&Validated {
+        output: output.clone(),
+        issues: issues.iter().map(ToString::to_string).collect(),
+    })?;
+    if count > 0 {
+        bail!(
+            "found {count} issue(s) in the Pages artifact {}",
+            display(&output)
+        );
+    }
+    session
+        .output
+        .status(format_args!("validated {}", display(&output)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{EXIT_FAILURE, EXIT_SUCCESS, EXIT_USAGE, run_in};
+    use super::*;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn write_repo(root: &Path) {
+        fs::create_dir(root.join("personas")).expect("personas dir");
+        fs::create_dir(root.join("scenarios")).expect("scenarios dir");
+        fs::create_dir(root.join("snippets")).expect("snippets dir");
+        fs::write(root.join("snippets").join("loop.md"), "- Run tests.\n").expect("snippet");
+        fs::write(
+            root.join("personas").join("ONE.md"),
+            "---\nid: one\nname: One\n---\n# One\n\n## Validation\n{{> snippets/loop.md}}\n- Ship **{{ id }}**.\n",
+        )
+        .expect("persona");
+        fs::write(
+            root.join("scenarios").join("AUDIT.md"),
+            "---\r\nid: audit\r\nname: Audit\r\n---\r\n# Audit\r\n",
+        )
+        .expect("scenario");
+    }
+
+    /// Adds every file the Pages manifest copies from the repository.
+    fn write_manifest(root: &Path) {
+        for file in crate::PAGES_MANIFEST {
+            if file.origin == crate::Origin::Repository {
+                let path = root.join(file.source);
+                fs::create_dir_all(path.parent().expect("parent")).expect("dir");
+                fs::write(path, "content\n").expect("file");
+            }
+        }
+    }
+
+    #[test]
+    fn render_writes_expanded_markdown_with_front_matter() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(tmp.path());
+        let output = tmp.path().join("public");
+
+        let (code, _, err) = run_in(tmp.path(), &["render", "--output", &display(&output)]);
+        assert_eq!(code, EXIT_SUCCESS, "{err}");
+
+        let persona = fs::read_to_string(output.join("personas").join("ONE.md")).expect("persona");
+        assert_eq!(
+            persona,
+            "---\nid: one\nname: One\n---\n# One\n\n## Validation\n- Run tests.\n- Ship **one**.\n"
+        );
+        let scenario =
+            fs::read_to_string(output.join("scenarios").join("AUDIT.md")).expect("scenario");
+        assert_eq!(scenario, "---\nid: audit\nname: Audit\n---\n# Audit\n");
+    }
+
+    #[test]
+    fn render_reports_missing_snippets() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(tmp.path());
+        fs::remove_file(tmp.path().join("snippets").join("loop.md")).expect("remove");
+
+        let output = display(&tmp.path().join("public"));
+        let (code, _, err) = run_in(tmp.path(), &["render", "--output", &output]);
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(err.contains("ONE.md:8:1"), "{err}");
+        assert!(err.contains("loop.md not found"), "{err}");
+    }
+
+    #[test]
+    fn render_requires_output() {
+        let err = RenderArgs::parse_from(Vec::new()).unwrap_err();
+        assert!(err.to_string().contains("--output <dir> is required"));
+        let err = RenderArgs::parse_from(vec!["--bogus".into()]).unwrap_err();
+        assert!(err.to_string().contains("unknown argument"));
+    }
+
+    #[test]
+    fn pages_build_writes_the_artifact() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(tmp.path());
+        write_manifest(tmp.path());
+
+        let (code, out, err) = run_in(tmp.path(), &["pages", "build", "--format", "json"]);
+        assert_eq!(code, EXIT_SUCCESS, "{err}");
+        let report: serde_json::Value = serde_json::from_str(&out).expect("json");
+        assert!(
+            report["files"]
+                .as_array()
+                .expect("files")
+                .contains(&"personas.json".into())
+        );
+        let persona =
+            fs::read_to_string(tmp.path().join("public/personas/ONE.md")).expect("persona");
+        assert!(persona.contains("- Run tests.\n"), "{persona}");
+
+        let base_url = ["--pages-base-url", "https://example.test"];
+        assert_eq!(
+            run_in(tmp.path(), &[&["pages", "build"][..], &base_url].concat()).0,
+            EXIT_SUCCESS
+        );
+        let (code, _, err) = run_in(
+            tmp.path(),
+            &[&["pages", "validate"][..], &base_url].concat(),
+        );
+        assert_eq!(code, EXIT_SUCCESS, "{err}");
+        assert!(err.contains("validated "), "{err}");
+
+        fs::remove_file(tmp.path().join("public/scenarios/AUDIT.md")).expect("remove");
+        let (code, out, err) = run_in(
+            tmp.path(),
+            &[&["pages", "validate", "--format", "json"][..], &base_url].concat(),
+        );
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(err.contains("found 1 issue(s)"), "{err}");
+        let report: serde_json::Value = serde_json::from_str(&out).expect("json");
+        assert!(
+            report["issues"][0]
+                .as_str()
+                .expect("issue")
+                .contains("scenarios/AUDIT.md")
+        );
+    }
+
+    #[test]
+    fn pages_build_publishes_overlay_roots_where_their_uris_point() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(tmp.path());
+        write_manifest(tmp.path());
+        let private = tmp.path().join("private");
+        fs::create_dir(&private).expect("private");
+        fs::write(
+            private.join("ONE.md"),
+            "---\nid: one\nname: One\n---\n# One\n{{> snippets/loop.md}}\n",
+        )
+        .expect("override");
+        fs::write(
+            private.join("EXTRA.md"),
+            "---\nid: extra\nname: Extra\n---\n# Extra\n",
+        )
+        .expect("overlay");
+
+        let args = ["--persona-root", "private"];
+        let (code, _, err) = run_in(tmp.path(), &[&args[..], &["pages", "build"]].concat());
+        assert_eq!(code, EXIT_SUCCESS, "{err}");
+        let (code, _, err) = run_in(tmp.path(), &[&args[..], &["pages", "validate"]].concat());
+        assert_eq!(code, EXIT_SUCCESS, "{err}");
+
+        let public = tmp.path().join("public");
+        assert!(!public.join("personas/ONE.md").exists());
+        assert_eq!(
+            fs::read_to_string(public.join("private/ONE.md")).expect("override"),
+            "---\nid: one\nname: One\n---\n# One\n- Run tests.\n"
+        );
+        let landing = fs::read_to_string(public.join("index.md")).expect("landing page");
+        assert!(
+            landing.contains("## Personas\n- [EXTRA](private/EXTRA.md)\n- [ONE](private/ONE.md)\n"),
+            "{landing}"
+        );
+    }
+
+    #[test]
+    fn pages_commands_report_missing_inputs() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(tmp.path());
+        let (code, _, err) = run_in(tmp.path(), &["pages", "build"]);
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(err.contains("AGENTS.md missing"), "{err}");
+        let (code, _, err) = run_in(tmp.path(), &["pages", "validate"]);
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(err.contains("public does not exist"), "{err}");
+        assert_eq!(run_in(tmp.path(), &["pages", "deploy"]).0, EXIT_USAGE);
+    }
+}
@@ -0,0 +1,162 @@
+use super::{Session, require_dir};
+use crate::PersonaEntry;
+use anyhow::Result;
+use std::path::Path;
+
+/// Regenerates `docs/PERSONA_AUDIT.md`, or checks it for drift with `--check`.
+pub(super) fn run_audit(session: &mut Session) -> Result<()> {
+    let config = &session.config;
+    require_dir(config.personas_dir(), "personas directory")?;
+    require_dir(parent(&config.audit_path), "docs directory")?;
+
+    let entries = session.persona_entries()?;
+    let outputs = vec![(session.config.audit_path.clone(), render_audit(entries))];
+    session.write_outputs("audit", outputs)
+}
+
+/// The audit table, listing personas by name.
+pub(super) fn render_audit(mut entries: Vec<PersonaEntry>) -> String {
+    entries.sort_by(|a, b| a.meta.name.cmp(&b.meta.name));
+    crate::render_persona_audit(&entries)
+}
+
+/// Regenerates the Mermaid and Graphviz handoff graphs, or checks them with `--check`.
+pub(super) fn run_handoffs(session: &mut Session) -> Result<()> {
+    let config = &session.config;
+    require_dir(config.personas_dir(), "personas directory")?;
+    require_dir(parent(&config.handoff_graph_path), "docs directory")?;
+
+    let entries = session.persona_entries()?;
+    let mermaid_path = session.config.handoff_graph_path.clone();
+    let dot_path = mermaid_path.with_extension("dot");
+    let outputs = vec![
+        (mermaid_path, crate::render_handoff_document(&entries)),
+        (dot_path, crate::render_handoff_dot(&entries)),
+    ];
+    session.write_outputs("handoffs", outputs)
+}
+
+pub(super) fn parent(path: &Path) -> &Path {
+    path.parent().unwrap_or(Path::new("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{EXIT_FAILURE, EXIT_STALE, EXIT_SUCCESS, run_in};
+    use std::fs;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn write_personas(root: &Path) {
+        let personas_dir = root.join("personas");
+        fs::create_dir(&personas_dir).expect("personas dir");
+        fs::create_dir(root.join("docs")).expect("docs dir");
+        fs::write(
+            personas_dir.join("ONE.md"),
+            "---\nid: one\nname: One\n---\n# One\n\n## When to Switch Away\n- Done → Two\n",
+        )
+        .expect("persona");
+        fs::write(
+            personas_dir.join("TWO.md"),
+            "---\nid: two\nname: Two\n---\n# Two\n\n## When to Switch Away\n- Blocked → One\n",
+        )
+        .expect("persona");
+    }
+
+    #[test]
+    fn audit_writes_table() {
+        let tmp = tempdir().expect("tempdir");
+        write_personas(tmp.path());
+
+        let (code, _, err) = run_in(tmp.path(), &["audit"]);
+        assert_eq!(code, EXIT_SUCCESS, "{err}");
+
+        let audit = fs::read_to_string(tmp.path().join("docs").join("PERSONA_AUDIT.md"))
+            .expect("audit contents");
+        assert!(audit.contains("Persona Audit"));
+        assert!(audit.contains("| One | one"));
+    }
+
+    #[test]
+    fn audit_check_detects_drift() {
+        let tmp = tempdir().expect("tempdir");
+        write_personas(tmp.path());
+        fs::write(tmp.path().join("docs").join("PERSONA_AUDIT.md"), "stale").expect("audit");
+
+        let (code, out, err) = run_in(tmp.path(), &["audit", "--check", "--format", "json"]);
+        assert_eq!(code, EXIT_STALE);
+        assert!(err.contains("PERSONA_AUDIT.md out of date"), "{err}");
+        let report: serde_json::Value = serde_json::from_str(&out).expect("json");
+        assert_eq!(report["stale"].as_array().map(Vec::len), Some(1));
+        assert_eq!(
+            fs::read_to_string(tmp.path().join("docs").join("PERSONA_AUDIT.md")).expect("audit"),
+            "stale"
+        );
+    }
+
+    #[test]
+    fn audit_writes_configured_path() {
+        let tmp = tempdir().expect("tempdir");
+        write_personas(tmp.path());
+        fs::create_dir(tmp.path().join("reports")).expect("reports dir");
+        fs::write(
+            tmp.path().join("codex-tools.yaml"),
+            "audit: reports/AUDIT.md\n",
+        )
+        .expect("config");
+
+        assert_eq!(run_in(tmp.path(), &["audit"]).0, EXIT_SUCCESS);
+
+        let audit = fs::read_to_string(tmp.path().join("reports").join("AUDIT.md")).expect("audit");
+        assert!(audit.contains("| One | one"), "{audit}");
+    }
+
+    #[test]
+    fn handoffs_write_mermaid_and_dot() {
+        let tmp = tempdir().expect("tempdir");
+        write_personas(tmp.path());
+
+        assert_eq!(run_in(tmp.path(), &["handoffs"]).0, EXIT_SUCCESS);
+
+        let docs_dir = tmp.path().join("docs");
+        let mermaid = fs::read_to_string(docs_dir.join("PERSONA_HANDOFFS.md")).expect("mermaid");
+        assert!(mermaid.contains("```mermaid\nflowchart LR\n"));
+        assert!(
+            mermaid.contains("    n0[\"One\"]\n    n1[\"Two\"]\n    n0 -->|\"Done\"| n1\n"),
+            "{mermaid}"
+        );
+        let dot = fs::read_to_string(docs_dir.join("PERSONA_HANDOFFS.dot")).expect("dot");
+        assert!(dot.contains("    \"two\" -> \"one\" [label=\"Blocked\"];\n"));
+
+        let (code, _, err) = run_in(tmp.path(), &["handoffs", "--check"]);
+        assert_eq!(code, EXIT_SUCCESS, "check after write: {err}");
+    }
+
+    #[test]
+    fn handoffs_check_detects_drift() {
+        let tmp = tempdir().expect("tempdir");
+        write_personas(tmp.path());
+        assert_eq!(run_in(tmp.path(), &["handoffs"]).0, EXIT_SUCCESS);
+        fs::write(
+            tmp.path().join("docs").join("PERSONA_HANDOFFS.dot"),
+            "stale",
+        )
+        .expect("dot");
+
+        let (code, _, err) = run_in(tmp.path(), &["handoffs", "--check"]);
+        assert_eq!(code, EXIT_STALE);
+        assert!(err.contains("PERSONA_HANDOFFS.dot out of date"));
+        assert!(!err.contains("PERSONA_HANDOFFS.md"));
+    }
+
+    #[test]
+    fn missing_docs_dir_returns_error() {
+        let tmp = tempdir().expect("tempdir");
+        fs::create_dir(tmp.path().join("personas")).expect("personas dir");
+        for command in ["audit", "handoffs"] {
+            let (code, _, err) = run_in(tmp.path(), &[command]);
+            assert_eq!(code, EXIT_FAILURE);
+            assert!(err.contains("docs directory missing"), "{err}");
+        }
+    }
+}
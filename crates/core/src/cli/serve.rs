@@ -0,0 +1,145 @@
+use super::pages::require_pages_sources;
+use super::{Session, display, require_dir};
+use crate::{PreviewServer, SiteRoot};
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Port used when `--port` is not given.
+const DEFAULT_PORT: u16 = 8000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ServeArgs {
+    /// A built artifact to serve; defaults to `public/` under the repository.
+    output: Option<PathBuf>,
+    /// Serve the repository itself, rebuilding the artifact on a request when a source
+    /// changed since the last one.
+    live: bool,
+    host: String,
+    port: u16,
+}
+
+impl ServeArgs {
+    pub(super) fn parse_from(args: Vec<String>) -> Result<Self> {
+        let mut parsed = Self {
+            output: None,
+            live: false,
+            host: "127.0.0.1".to_string(),
+            port: DEFAULT_PORT,
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--output" => {
+                    parsed.output = Some(PathBuf::from(
+                        args.next().context("--output requires a value")?,
+                    ))
+                }
+                "--live" => parsed.live = true,
+                "--host" => parsed.host = args.next().context("--host requires a value")?,
+                "--port" => {
+                    let port = args.next().context("--port requires a value")?;
+                    parsed.port = port
+                        .parse()
+                        .with_context(|| format!("invalid port: {port}"))?;
+                }
+                _ => bail!("unknown argument: {arg}"),
+            }
+        }
+        if parsed.live && parsed.output.is_some() {
+            bail!("--live serves the repository and cannot be combined with --output");
+        }
+        Ok(parsed)
+    }
+}
+
+/// Serves the Pages artifact, or with `--live` the repository, until interrupted.
+pub(super) fn run_serve(session: &mut Session, args: &ServeArgs) -> Result<()> {
+    let root = if args.live {
+        require_pages_sources(&session.config)?;
+        SiteRoot::live(&session.repo_root, session.config.clone())
+    } else {
+        let output = session
+            .repo_root
+            .join(args.output.as_deref().unwrap_or("public".as_ref()));
+        require_dir(
+            &output,
+            "Pages artifact (run `codex-tools pages build` first)",
+        )?;
+        SiteRoot::Artifact(output)
+    };
+    let address = format!("{}:{}", args.host, args.port);
+    let server =
+        PreviewServer::bind(&address, root.clone()).with_context(|| format!("bind {address}"))?;
+    let url = server.url().context("read the listening address")?;
+
+    #[derive(Serialize)]
+    struct Serving<'a> {
+        url: &'a str,
+        root: &'a PathBuf,
+    }
+    let served = match &root {
+        SiteRoot::Artifact(output) => output,
+        SiteRoot::Live { repo_root, .. } => repo_root,
+    };
+    session.output.text(&format!("{url}\n"))?;
+    session.output.json(&Serving {
+        url: &url,
+        root: served,
+    })?;
+    session.output.status(format_args!(
+        "serving {} at {url}; press Ctrl-C to stop",
+        display(served)
+    ))?;
+
+    server
+        .run(|exchange| {
+            let _ = session.output.status(format_args!(
+                "{} {} {}",
+                exchange.method, exchange.path, exchange.status
+            ));
+        })
+        .with_context(|| format!("serve {url}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{EXIT_FAILURE, run_in};
+    use super::*;
+    use std::fs;
+    use std::net::TcpListener;
+    use tempfile::tempdir;
+
+    #[test]
+    fn parses_serve_options() {
+        let args = ServeArgs::parse_from(vec!["--live".into(), "--port".into(), "0".into()])
+            .expect("args");
+        assert!(args.live);
+        assert_eq!((args.host.as_str(), args.port), ("127.0.0.1", 0));
+        assert_eq!(
+            ServeArgs::parse_from(Vec::new()).expect("args").port,
+            DEFAULT_PORT
+        );
+
+        let err = ServeArgs::parse_from(vec!["--port".into(), "http".into()]).unwrap_err();
+        assert!(err.to_string().contains("invalid port: http"), "{err}");
+        let err = ServeArgs::parse_from(vec!["--live".into(), "--output".into(), "site".into()])
+            .unwrap_err();
+        assert!(err.to_string().contains("cannot be combined"), "{err}");
+    }
+
+    #[test]
+    fn serve_reports_missing_artifacts_and_busy_ports() {
+        let tmp = tempdir().expect("tempdir");
+        let (code, _, err) = run_in(tmp.path(), &["serve"]);
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(err.contains("run `codex-tools pages build` first"), "{err}");
+
+        fs::create_dir(tmp.path().join("public")).expect("public");
+        let taken = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let port = taken.local_addr().expect("addr").port().to_string();
+        let (code, _, err) = run_in(tmp.path(), &["serve", "--port", &port]);
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(err.contains(&format!("bind 127.0.0.1:{port}")), "{err}");
+    }
+}
@@ -0,0 +1,298 @@
+use super::catalog::diagnostics;
+use super::reports::{parent, render_audit};
+use super::{Session, display, normalize_line_endings, require_dir};
+use crate::cache::Snapshot;
+use crate::{Diagnostic, SourceCache};
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// How often the watched files are polled when `--interval` is not given.
+const DEFAULT_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct WatchArgs {
+    interval: Duration,
+}
+
+impl WatchArgs {
+    pub(super) fn parse_from(args: Vec<String>) -> Result<Self> {
+        let mut interval = DEFAULT_INTERVAL;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--interval" => {
+                    let millis = args.next().context("--interval requires milliseconds")?;
+                    interval = match millis.parse() {
+                        Ok(millis) if millis > 0 => Duration::from_millis(millis),
+                        _ => bail!("invalid interval: {millis} (expected milliseconds above 0)"),
+                    };
+                }
+                _ => bail!("unknown argument: {arg}"),
+            }
+        }
+        Ok(Self { interval })
+    }
+}
+
+/// Regenerates both catalogs and the persona audit, then again whenever a persona,
+/// scenario, included snippet, or `AGENTS.md` changes, until interrupted.
+pub(super) fn run_watch(session: &mut Session, args: &WatchArgs) -> Result<()> {
+    let config = &session.config;
+    require_dir(config.personas_dir(), "personas directory")?;
+    if !config.agents_path.is_file() {
+        bail!("AGENTS.md missing: {}", display(&config.agents_path));
+    }
+    require_dir(config.scenarios_dir(), "scenarios directory")?;
+    require_dir(parent(&config.audit_path), "docs directory")?;
+
+    let mut cache = SourceCache::default();
+    let mut snapshot = Snapshot::take(&session.config, &[]);
+    let mut changed = Vec::new();
+    session.output.status(format_args!(
+        "watching {}, {}, and {}; press Ctrl-C to stop",
+        display(session.config.personas_dir()),
+        display(session.config.scenarios_dir()),
+        display(&session.config.agents_path)
+    ))?;
+    loop {
+        // A broken source is reported and fixed in the editor; the watch keeps going.
+        if let Err(error) = regenerate(session, &mut cache, changed) {
+            writeln!(session.output.err, "error: {error:#}").context("write output")?;
+        }
+        // Snippets are known once the sources are parsed, and are stamped as they were then.
+        let includes = cache.includes();
+        changed = loop {
+            thread::sleep(args.interval);
+            let next = Snapshot::take(&session.config, &[]);
+            let mut changes = next.changes_since(&snapshot);
+            changes.extend(includes.refresh().changes_since(&includes));
+            if !changes.is_empty() {
+                changes.sort();
+                changes.dedup();
+                snapshot = next;
+                break changes;
+            }
+        };
+        for path in &changed {
+            session
+                .output
+                .status(format_args!("changed {}", display(path)))?;
+        }
+    }
+}
+
+/// The JSON result of one regeneration.
+#[derive(Debug, Serialize)]
+struct Regenerated {
+    changed: Vec<PathBuf>,
+    /// Sources parsed again; unchanged ones come from the cache.
+    parsed: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    written: Vec<PathBuf>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Collects the sources through `cache` and rewrites the outputs whose contents changed.
+fn regenerate(session: &mut Session, cache: &mut SourceCache, changed: Vec<PathBuf>) -> Result<()> {
+    let config = &session.config;
+    let options = config.catalog_options();
+    let personas =
+        crate::collect::<crate::PersonaSource>(&config.persona_roots, &options, Some(cache));
+    let scenarios =
+        crate::collect::<crate::ScenarioSource>(&config.scenario_roots, &options, Some(cache));
+    let mut report = Regenerated {
+        changed,
+        parsed: cache.parsed(),
+        written: Vec::new(),
+        diagnostics: diagnostics(&personas)
+            .chain(diagnostics(&scenarios))
+            .collect(),
+    };
+    session.output.diagnostics(&report.diagnostics)?;
+    let errors = personas.errors.len() + scenarios.errors.len();
+    if errors > 0 {
+        session.output.json(&report)?;
+        bail!("found {errors} error(s) in persona and scenario sources; outputs left unchanged");
+    }
+
+    let personas = personas
+        .sources
+        .into_iter()
+        .map(|source| source.entry)
+        .collect::<Vec<_>>();
+    let scenarios = scenarios
+        .sources
+        .into_iter()
+        .map(|source| source.entry)
+        .collect();
+    let persona_catalog = config.personas_dir().join("catalog.json");
+    let index = crate::build_index(
+        config.personas_dir(),
+        &config.agents_path,
+        personas.clone(),
+        &options,
+    )
+    .with_context(|| format!("generate {}", display(&persona_catalog)))?;
+    let scenario_catalog = config.scenarios_dir().join("catalog.json");
+    let scenario_index = crate::build_scenario_index(
+        config.scenarios_dir(),
+        &config.agents_path,
+        scenarios,
+        &options,
+    )
+    .with_context(|| format!("generate {}", display(&scenario_catalog)))?;
+    let outputs = [
+        (persona_catalog, crate::render_catalog(&index)?),
+        (scenario_catalog, crate::render_catalog(&scenario_index)?),
+        (config.audit_path.clone(), render_audit(personas)),
+    ];
+
+    for (path, contents) in outputs {
+        if write_if_changed(&path, &contents)? {
+            session
+                .output
+                .status(format_args!("wrote {}", display(&path)))?;
+            report.written.push(path);
+        }
+    }
+    if report.written.is_empty() {
+        session.output.status("outputs up to date")?;
+    }
+    session.output.json(&report)
+}
+
+/// Writes `contents` unless the file already holds them, ignoring line endings.
+fn write_if_changed(path: &Path, contents: &str) -> Result<bool> {
+    match fs::read_to_string(path) {
+        Ok(current) if normalize_line_endings(&current) == normalize_line_endings(contents) => {
+            return Ok(false);
+        }
+        Ok(_) => {}
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err).with_context(|| format!("read {}", display(path))),
+    }
+    fs::write(path, contents).with_context(|| format!("write {}", display(path)))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{EXIT_FAILURE, EXIT_USAGE, Format, MessageFormat, Output, run_in};
+    use super::*;
+    use crate::RepoConfig;
+    use tempfile::tempdir;
+
+    fn write_repo(root: &Path) {
+        for dir in ["personas", "scenarios", "docs"] {
+            fs::create_dir(root.join(dir)).expect("dir");
+        }
+        fs::write(root.join("AGENTS.md"), "# Agents\n").expect("agents");
+        fs::write(
+            root.join("personas/ONE.md"),
+            "---\nid: one\nname: One\n---\n# One\n",
+        )
+        .expect("persona");
+        fs::write(
+            root.join("scenarios/AUDIT.md"),
+            "---\nid: audit\nname: Audit\n---\n# Audit\n",
+        )
+        .expect("scenario");
+    }
+
+    /// Runs one regeneration in `root`, returning its result and stderr.
+    fn regenerate_in(root: &Path, cache: &mut SourceCache) -> (Result<()>, String) {
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        let mut session = Session {
+            repo_root: root.to_path_buf(),
+            config: RepoConfig::defaults(root),
+            check: false,
+            output: Output {
+                format: Format::Text,
+                message_format: MessageFormat::Human,
+                quiet: false,
+                out: &mut out,
+                err: &mut err,
+            },
+        };
+        let result = regenerate(&mut session, cache, Vec::new());
+        drop(session);
+        (result, String::from_utf8(err).expect("utf8"))
+    }
+
+    #[test]
+    fn rewrites_only_outputs_whose_contents_changed() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(tmp.path());
+        let mut cache = SourceCache::default();
+
+        let (result, err) = regenerate_in(tmp.path(), &mut cache);
+        result.expect("first run");
+        assert_eq!(err.matches("wrote ").count(), 3, "{err}");
+
+        let (result, err) = regenerate_in(tmp.path(), &mut cache);
+        result.expect("second run");
+        assert!(!err.contains("wrote "), "{err}");
+        assert!(err.ends_with("outputs up to date\n"), "{err}");
+        // Warnings are repeated for cached files too.
+        assert!(err.contains("no `tags` in front matter"), "{err}");
+        assert_eq!(cache.parsed(), 0);
+
+        fs::write(
+            tmp.path().join("personas/ONE.md"),
+            "---\nid: one\nname: Uno\n---\n# Uno\n",
+        )
+        .expect("persona");
+        let (result, err) = regenerate_in(tmp.path(), &mut cache);
+        result.expect("after edit");
+        assert_eq!(cache.parsed(), 1);
+        assert!(err.contains("personas/catalog.json"), "{err}");
+        assert!(err.contains("PERSONA_AUDIT.md"), "{err}");
+        assert!(!err.contains("scenarios/catalog.json"), "{err}");
+        let catalog =
+            fs::read_to_string(tmp.path().join("personas/catalog.json")).expect("catalog");
+        assert!(catalog.contains("\"name\": \"Uno\""), "{catalog}");
+    }
+
+    #[test]
+    fn broken_sources_are_reported_without_touching_outputs() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(tmp.path());
+        let mut cache = SourceCache::default();
+        regenerate_in(tmp.path(), &mut cache).0.expect("first run");
+        let before = fs::read_to_string(tmp.path().join("personas/catalog.json")).expect("catalog");
+
+        fs::write(tmp.path().join("personas/TWO.md"), "no front matter\n").expect("persona");
+        let (result, err) = regenerate_in(tmp.path(), &mut cache);
+        let error = result.expect_err("broken persona");
+        assert!(
+            error.to_string().contains("outputs left unchanged"),
+            "{error}"
+        );
+        assert!(err.contains("TWO.md"), "{err}");
+        assert_eq!(
+            fs::read_to_string(tmp.path().join("personas/catalog.json")).expect("catalog"),
+            before
+        );
+    }
+
+    #[test]
+    fn watch_validates_arguments_and_layout_before_starting() {
+        let err = WatchArgs::parse_from(vec!["--interval".into(), "0".into()]).unwrap_err();
+        assert!(err.to_string().contains("invalid interval: 0"), "{err}");
+        assert_eq!(
+            WatchArgs::parse_from(Vec::new()).expect("args").interval,
+            DEFAULT_INTERVAL
+        );
+
+        let tmp = tempdir().expect("tempdir");
+        assert_eq!(run_in(tmp.path(), &["watch", "--check"]).0, EXIT_USAGE);
+        let (code, _, err) = run_in(tmp.path(), &["watch"]);
+        assert_eq!(code, EXIT_FAILURE);
+        assert!(err.contains("personas directory missing"), "{err}");
+    }
+}
@@ -0,0 +1,223 @@
+use crate::{CatalogError, Document, RepoConfig, Version};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// One Markdown source contributing to a composed prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptPart<'a> {
+    /// Path shown in the provenance header, relative to the repository root.
+    pub label: String,
+    /// Catalog id, absent for the shared `AGENTS.md` instructions.
+    pub id: Option<&'a str>,
+    pub version: Option<Version>,
+    pub body: &'a str,
+    /// 1-based line of the source file on which `body` starts.
+    pub first_line: usize,
+}
+
+/// Joins `parts` in order under a provenance header, dropping repeated sections.
+///
+/// A level-two section is dropped when an earlier part already contained a
+/// section with the same heading and the same (whitespace-insensitive) content.
+pub fn compose_prompt(parts: &[PromptPart<'_>]) -> String {
+    let mut output = String::from("<!--\nComposed prompt. Sources, in order:\n");
+    for part in parts {
+        output.push_str(&format!("- {}", part.label));
+        match (part.id, part.version) {
+            (Some(id), Some(version)) => {
+                output.push_str(&format!(" (id: {id}, version: {version})"))
+            }
+            (Some(id), None) => output.push_str(&format!(" (id: {id})")),
+            (None, _) => {}
+        }
+        output.push('\n');
+    }
+    output.push_str("-->\n");
+
+    let mut seen = HashSet::new();
+    for part in parts {
+        let document = Document::parse(part.body, part.first_line);
+        let mut skipped = Vec::new();
+        for section in &document.sections {
+            let key = (section.heading.text.clone(), normalize(&section.content));
+            if !seen.insert(key) {
+                let start = section.heading.line;
+                skipped.push(start..start + 1 + section.content.lines().count());
+            }
+        }
+        let text = part
+            .body
+            .lines()
+            .enumerate()
+            .filter(|(offset, _)| {
+                let line = part.first_line + offset;
+                !skipped.iter().any(|range| range.contains(&line))
+            })
+            .map(|(_, line)| format!("{line}\n"))
+            .collect::<String>();
+        let text = text.trim();
+        if !text.is_empty() {
+            output.push('\n');
+            output.push_str(text);
+            output.push('\n');
+        }
+    }
+    output
+}
+
+fn normalize(content: &str) -> String {
+    content.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Composes `AGENTS.md`, the persona `persona_id`, and optionally the scenario
+/// `scenario_id` from the layout configured for `repo_root` (see [`RepoConfig`]).
+pub fn compose(
+    repo_root: &Path,
+    persona_id: &str,
+    scenario_id: Option<&str>,
+) -> Result<String, CatalogError> {
+    let config = RepoConfig::load(repo_root, None)?;
+    compose_with(repo_root, &config, persona_id, scenario_id)
+}
+
+/// Like [`compose`], reading sources from the roots in an already resolved `config`.
+pub fn compose_with(
+    repo_root: &Path,
+    config: &RepoConfig,
+    persona_id: &str,
+    scenario_id: Option<&str>,
+) -> Result<String, CatalogError> {
+    let options = config.catalog_options();
+    let agents_path = &config.agents_path;
+    let agents =
+        fs::read_to_string(agents_path).map_err(|source| CatalogError::io(agents_path, source))?;
+
+    let personas_dir = config.personas_dir();
+    let personas = crate::collect::<crate::PersonaSource>(&config.persona_roots, &options, None)
+        .into_result()?;
+    let persona = personas
+        .iter()
+        .find(|source| source.entry.meta.id == persona_id)
+        .ok_or_else(|| CatalogError::unknown_id("persona", persona_id, personas_dir))?;
+
+    let scenarios_dir = config.scenarios_dir();
+    let scenarios = match scenario_id {
+        Some(_) => crate::collect::<crate::ScenarioSource>(&config.scenario_roots, &options, None)
+            .into_result()?,
+        None => Vec::new(),
+    };
+    let scenario = scenario_id
+        .map(|id| {
+            scenarios
+                .iter()
+                .find(|source| source.entry.meta.id == id)
+                .ok_or_else(|| CatalogError::unknown_id("scenario", id, scenarios_dir))
+        })
+        .transpose()?;
+
+    let label = |path: &Path| {
+        path.strip_prefix(repo_root)
+            .unwrap_or(path)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    };
+    let mut parts = vec![
+        PromptPart {
+            label: label(agents_path),
+            id: None,
+            version: None,
+            body: &agents,
+            first_line: 1,
+        },
+        PromptPart {
+            label: label(&persona.path),
+            id: Some(&persona.entry.meta.id),
+            version: persona.entry.meta.version,
+            body: &persona.body,
+            first_line: persona.body_line,
+        },
+    ];
+    if let Some(scenario) = scenario {
+        parts.push(PromptPart {
+            label: label(&scenario.path),
+            id: Some(&scenario.entry.meta.id),
+            version: scenario.entry.meta.version,
+            body: &scenario.body,
+            first_line: scenario.body_line,
+        });
+    }
+    Ok(compose_prompt(&parts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+    use tempfile::tempdir;
+
+    #[test]
+    fn orders_parts_and_drops_repeated_sections() {
+        let parts = [
+            PromptPart {
+                label: "AGENTS.md".into(),
+                id: None,
+                version: None,
+                body: "# Base\n\n## Style\nBe brief.\n",
+                first_line: 1,
+            },
+            PromptPart {
+                label: "personas/ONE.md".into(),
+                id: Some("one"),
+                version: Some("0.2".parse().expect("version")),
+                body: "# One\n\n## Style\nBe  brief.\n\n## Role Snapshot\nShip it.\n",
+                first_line: 5,
+            },
+            PromptPart {
+                label: "scenarios/AUDIT.md".into(),
+                id: Some("audit"),
+                version: None,
+                body: "# Audit\n\n## Style\nBe thorough.\n",
+                first_line: 4,
+            },
+        ];
+
+        assert_eq!(
+            compose_prompt(&parts),
+            "<!--\nComposed prompt. Sources, in order:\n- AGENTS.md\n- personas/ONE.md (id: one, version: 0.2)\n- scenarios/AUDIT.md (id: audit)\n-->\n\n# Base\n\n## Style\nBe brief.\n\n# One\n\n## Role Snapshot\nShip it.\n\n# Audit\n\n## Style\nBe thorough.\n"
+        );
+    }
+
+    #[test]
+    fn resolves_ids_through_the_catalog() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let root = tmp.path();
+        fs::create_dir(root.join("personas"))?;
+        fs::create_dir(root.join("scenarios"))?;
+        fs::write(root.join("AGENTS.md"), "# Base\n")?;
+        fs::write(
+            root.join("personas").join("ONE.md"),
+            "---\nid: one\nname: One\nversion: 0.10\n---\n# One\n",
+        )?;
+        fs::write(
+            root.join("scenarios").join("AUDIT.md"),
+            "---\nid: audit\nname: Audit\n---\n# Audit\n",
+        )?;
+
+        let prompt = compose(root, "one", Some("audit"))?;
+        assert!(prompt.contains("- personas/ONE.md (id: one, version: 0.10)\n"));
+        assert!(prompt.ends_with("# Base\n\n# One\n\n# Audit\n"));
+
+        let err = compose(root, "one", Some("missing")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "no scenario with id `missing` in {}",
+                root.join("scenarios").display()
+            )
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,336 @@
+use crate::{CatalogError, CatalogOptions};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Optional configuration file read from the repository root.
+pub const CONFIG_FILE: &str = "codex-tools.yaml";
+
+/// Published site root used when neither the configuration nor `PAGES_BASE_URL` sets one.
+pub const DEFAULT_PAGES_BASE_URL: &str = "https://qqrm.github.io/codex-tools";
+
+/// Order of the entries written to `catalog.json`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Id,
+    Name,
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "id" => Ok(Self::Id),
+            "name" => Ok(Self::Name),
+            _ => Err(format!(
+                "unknown sort order: {value} (expected `id` or `name`)"
+            )),
+        }
+    }
+}
+
+/// Why a configuration was rejected.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error(transparent)]
+    Yaml(#[from] serde_yaml_ng::Error),
+    #[error(
+        "{} has the same catalog name `{name}` as {}; give one of them another name in `root_names`",
+        root.display(),
+        other.display()
+    )]
+    DuplicateRootName {
+        root: PathBuf,
+        name: String,
+        other: PathBuf,
+    },
+    #[error("`{value}` is not {expected}")]
+    InvalidValue {
+        value: String,
+        expected: &'static str,
+    },
+}
+
+/// Generator settings, resolved from lowest to highest precedence:
+/// built-in defaults, [`CONFIG_FILE`], environment variables, then command-line flags
+/// (which the binaries apply to the loaded value).
+///
+/// Every path is absolute, resolved against the repository root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoConfig {
+    /// The repository itself; snippet includes resolve against it.
+    pub repo_root: PathBuf,
+    /// Persona directories in increasing precedence; the first is the primary one.
+    pub persona_roots: Vec<PathBuf>,
+    /// Scenario directories in increasing precedence; the first is the primary one.
+    pub scenario_roots: Vec<PathBuf>,
+    /// Catalog names for roots, such as a private checkout outside the repository.
+    pub root_names: BTreeMap<PathBuf, String>,
+    pub agents_path: PathBuf,
+    pub audit_path: PathBuf,
+    /// The Mermaid handoff graph; the Graphviz copy sits next to it with a `.dot` extension.
+    pub handoff_graph_path: PathBuf,
+    pub pages_base_url: String,
+    pub strict: bool,
+    /// Custom front matter keys that strict mode accepts.
+    pub custom_fields: Vec<String>,
+    pub namespace_ids: bool,
+    pub sort: SortOrder,
+}
+
+/// The on-disk shape of [`CONFIG_FILE`], and of the environment layer; every key is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    personas: Option<Vec<PathBuf>>,
+    scenarios: Option<Vec<PathBuf>>,
+    root_names: Option<BTreeMap<PathBuf, String>>,
+    agents: Option<PathBuf>,
+    audit: Option<PathBuf>,
+    handoff_graph: Option<PathBuf>,
+    pages_base_url: Option<String>,
+    strict: Option<bool>,
+    custom_fields: Option<Vec<String>>,
+    namespace_ids: Option<bool>,
+    sort: Option<SortOrder>,
+}
+
+impl ConfigFile {
+    fn from_env(var: impl Fn(&str) -> Option<String>) -> Result<Self, CatalogError> {
+        let var = |name: &str| var(name).filter(|value| !value.is_empty());
+        let roots = |value: String| env::split_paths(&value).collect();
+        Ok(Self {
+            personas: var("CODEX_TOOLS_PERSONAS").map(roots),
+            scenarios: var("CODEX_TOOLS_SCENARIOS").map(roots),
+            root_names: None,
+            agents: var("CODEX_TOOLS_AGENTS").map(PathBuf::from),
+            audit: var("CODEX_TOOLS_AUDIT").map(PathBuf::from),
+            handoff_graph: var("CODEX_TOOLS_HANDOFF_GRAPH").map(PathBuf::from),
+            pages_base_url: var("PAGES_BASE_URL"),
+            strict: parse_var(var, "CODEX_TOOLS_STRICT", "`true` or `false`")?,
+            custom_fields: var("CODEX_TOOLS_CUSTOM_FIELDS").map(|value| {
+                value
+                    .split(',')
+                    .map(|field| field.trim().to_string())
+                    .filter(|field| !field.is_empty())
+                    .collect()
+            }),
+            namespace_ids: parse_var(var, "CODEX_TOOLS_NAMESPACE_IDS", "`true` or `false`")?,
+            sort: parse_var(var, "CODEX_TOOLS_SORT", "`id` or `name`")?,
+        })
+    }
+}
+
+/// Parses the environment variable `name`, if set, describing `expected` on failure.
+fn parse_var<T: FromStr>(
+    var: impl Fn(&str) -> Option<String>,
+    name: &str,
+    expected: &'static str,
+) -> Result<Option<T>, CatalogError> {
+    var(name)
+        .map(|value| {
+            value.parse().map_err(|_| {
+                CatalogError::config(
+                    Path::new(name),
+                    ConfigError::InvalidValue { value, expected },
+                )
+            })
+        })
+        .transpose()
+}
+
+impl RepoConfig {
+    /// The built-in layout: `personas/`, `scenarios/`, `AGENTS.md`, and `docs/`.
+    pub fn defaults(repo_root: &Path) -> Self {
+        Self {
+            repo_root: repo_root.to_path_buf(),
+            persona_roots: vec![repo_root.join("personas")],
+            scenario_roots: vec![repo_root.join("scenarios")],
+            root_names: BTreeMap::new(),
+            agents_path: repo_root.join("AGENTS.md"),
+            audit_path: repo_root.join("docs").join("PERSONA_AUDIT.md"),
+            handoff_graph_path: repo_root.join("docs").join("PERSONA_HANDOFFS.md"),
+            pages_base_url: DEFAULT_PAGES_BASE_URL.to_string(),
+            strict: false,
+            custom_fields: Vec::new(),
+            namespace_ids: false,
+            sort: SortOrder::default(),
+        }
+    }
+
+    /// Loads the defaults, then `config_path` (or `repo_root/codex-tools.yaml` when it
+    /// exists), then the environment: `PAGES_BASE_URL` and a `CODEX_TOOLS_<KEY>` variable
+    /// for every other key except `root_names`, such as `CODEX_TOOLS_SORT=name`. `CODEX_TOOLS_PERSONAS` and
+    /// `CODEX_TOOLS_SCENARIOS` list roots separated like `PATH`.
+    pub fn load(repo_root: &Path, config_path: Option<&Path>) -> Result<Self, CatalogError> {
+        Self::load_with_env(repo_root, config_path, |name| env::var(name).ok())
+    }
+
+    fn load_with_env(
+        repo_root: &Path,
+        config_path: Option<&Path>,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, CatalogError> {
+        let mut config = Self::defaults(repo_root);
+        let path = match config_path {
+            Some(path) => Some(repo_root.join(path)),
+            None => Some(repo_root.join(CONFIG_FILE)).filter(|path| path.is_file()),
+        };
+        if let Some(path) = path {
+            let content =
+                fs::read_to_string(&path).map_err(|source| CatalogError::io(&path, source))?;
+            if !content.trim().is_empty() {
+                let file: ConfigFile = serde_yaml_ng::from_str(&content)
+                    .map_err(|source| CatalogError::config(&path, source))?;
+                config.apply_file(repo_root, file);
+            }
+        }
+        let env = ConfigFile::from_env(var)?;
+        config.apply_file(repo_root, env);
+        Ok(config)
+    }
+
+    fn apply_file(&mut self, repo_root: &Path, file: ConfigFile) {
+        let resolve = |paths: Vec<PathBuf>| paths.into_iter().map(|path| repo_root.join(path));
+        if let Some(personas) = file.personas.filter(|roots| !roots.is_empty()) {
+            self.persona_roots = resolve(personas).collect();
+        }
+        if let Some(scenarios) = file.scenarios.filter(|roots| !roots.is_empty()) {
+            self.scenario_roots = resolve(scenarios).collect();
+        }
+        if let Some(names) = file.root_names {
+            self.root_names = names
+                .into_iter()
+                .map(|(root, name)| (repo_root.join(root), name))
+                .collect();
+        }
+        if let Some(agents) = file.agents {
+            self.agents_path = repo_root.join(agents);
+        }
+        if let Some(audit) = file.audit {
+            self.audit_path = repo_root.join(audit);
+        }
+        if let Some(graph) = file.handoff_graph {
+            self.handoff_graph_path = repo_root.join(graph);
+        }
+        if let Some(url) = file.pages_base_url {
+            self.pages_base_url = url;
+        }
+        self.strict = file.strict.unwrap_or(self.strict);
+        if let Some(fields) = file.custom_fields {
+            self.custom_fields = fields;
+        }
+        self.namespace_ids = file.namespace_ids.unwrap_or(self.namespace_ids);
+        self.sort = file.sort.unwrap_or(self.sort);
+    }
+
+    /// The primary persona directory, which receives `catalog.json`.
+    pub fn personas_dir(&self) -> &Path {
+        &self.persona_roots[0]
+    }
+
+    /// The primary scenario directory, which receives `catalog.json`.
+    pub fn scenarios_dir(&self) -> &Path {
+        &self.scenario_roots[0]
+    }
+
+    pub fn catalog_options(&self) -> CatalogOptions {
+        CatalogOptions {
+            strict: self.strict,
+            custom_fields: self.custom_fields.clone(),
+            namespace_ids: self.namespace_ids,
+            root_names: self.root_names.clone(),
+            pages_base_url: Some(self.pages_base_url.clone()),
+            sort: self.sort,
+            structure: false,
+            repo_root: Some(self.repo_root.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+    use tempfile::tempdir;
+
+    #[test]
+    fn layers_file_and_environment_over_defaults() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let root = tmp.path();
+        let no_env = |_: &str| None;
+        assert_eq!(
+            RepoConfig::load_with_env(root, None, no_env)?,
+            RepoConfig::defaults(root)
+        );
+
+        fs::write(
+            root.join(CONFIG_FILE),
+            "personas: [personas, private/personas]\naudit: reports/AUDIT.md\npages_base_url: https://file.invalid\nstrict: true\ncustom_fields: [model_hints]\nsort: name\nroot_names:\n  ../private-personas: private\n",
+        )?;
+        let config = RepoConfig::load_with_env(root, None, no_env)?;
+        assert_eq!(
+            config.persona_roots,
+            vec![root.join("personas"), root.join("private/personas")]
+        );
+        assert_eq!(config.audit_path, root.join("reports/AUDIT.md"));
+        assert_eq!(config.scenarios_dir(), root.join("scenarios"));
+        assert_eq!(config.pages_base_url, "https://file.invalid");
+        assert!(config.strict);
+        assert_eq!(config.custom_fields, ["model_hints"]);
+        assert_eq!(
+            config.root_names,
+            BTreeMap::from([(root.join("../private-personas"), "private".to_string())])
+        );
+        assert_eq!(config.sort, SortOrder::Name);
+
+        let env = |name: &str| match name {
+            "PAGES_BASE_URL" => Some("https://env.invalid".into()),
+            "CODEX_TOOLS_SCENARIOS" => env::join_paths(["scenarios", "private/scenarios"])
+                .ok()
+                .and_then(|paths| paths.into_string().ok()),
+            "CODEX_TOOLS_AUDIT" => Some("out/AUDIT.md".into()),
+            "CODEX_TOOLS_STRICT" => Some("false".into()),
+            "CODEX_TOOLS_SORT" => Some("id".into()),
+            "CODEX_TOOLS_NAMESPACE_IDS" => Some(String::new()),
+            _ => None,
+        };
+        let config = RepoConfig::load_with_env(root, None, env)?;
+        assert_eq!(config.pages_base_url, "https://env.invalid");
+        assert_eq!(
+            config.scenario_roots,
+            vec![root.join("scenarios"), root.join("private/scenarios")]
+        );
+        assert_eq!(config.audit_path, root.join("out/AUDIT.md"));
+        assert!(!config.strict);
+        assert_eq!(config.sort, SortOrder::Id);
+        assert_eq!(config.persona_roots.len(), 2);
+
+        let env = |name: &str| (name == "CODEX_TOOLS_SORT").then(|| "size".into());
+        let err = RepoConfig::load_with_env(root, None, env).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid configuration in CODEX_TOOLS_SORT: `size` is not `id` or `name`"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_unknown_keys() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        fs::write(tmp.path().join("custom.yaml"), "stirct: true\n")?;
+        let err = RepoConfig::load_with_env(tmp.path(), Some(Path::new("custom.yaml")), |_| None)
+            .unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("custom.yaml"), "{message}");
+        assert!(message.contains("unknown field `stirct`"), "{message}");
+        Ok(())
+    }
+}
@@ -0,0 +1,373 @@
+use crate::{CatalogError, CatalogWarning, FrontMatterError};
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A 1-based line and column in a source file, with the text of that line when known.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceSpan {
+    pub line: usize,
+    pub column: usize,
+    pub snippet: Option<String>,
+}
+
+impl SourceSpan {
+    /// Builds a span for `line`/`column`, capturing the matching line of `content` as the snippet.
+    pub fn at(content: &str, line: usize, column: usize) -> Self {
+        let snippet = content.lines().nth(line.saturating_sub(1)).map(|text| {
+            text.trim_start_matches('\u{FEFF}')
+                .trim_end_matches('\r')
+                .to_string()
+        });
+        Self {
+            line,
+            column,
+            snippet,
+        }
+    }
+
+    fn line_only(line: usize) -> Self {
+        Self {
+            line,
+            column: 1,
+            snippet: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A single located message ready to be rendered for people or tools.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub path: PathBuf,
+    pub span: Option<SourceSpan>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(severity: Severity, path: &Path, span: Option<SourceSpan>, message: String) -> Self {
+        Self {
+            severity,
+            path: path.to_path_buf(),
+            span,
+            message,
+        }
+    }
+
+    /// Renders a multi-line report with the offending line and a caret under the column.
+    pub fn render_human(&self) -> String {
+        let mut output = format!("{}: {}\n", self.severity, self.message);
+        let Some(span) = &self.span else {
+            output.push_str(&format!(" --> {}\n", self.path.display()));
+            return output;
+        };
+        output.push_str(&format!(
+            " --> {}:{}:{}\n",
+            self.path.display(),
+            span.line,
+            span.column
+        ));
+        if let Some(snippet) = &span.snippet {
+            let gutter = " ".repeat(span.line.to_string().len());
+            // Keep tabs so the caret lines up with the snippet in terminals.
+            let padding = snippet
+                .chars()
+                .take(span.column.saturating_sub(1))
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect::<String>();
+            output.push_str(&format!("{gutter} |\n"));
+            output.push_str(&format!("{} | {snippet}\n", span.line));
+            output.push_str(&format!("{gutter} | {padding}^\n"));
+        }
+        output
+    }
+
+    /// Renders a single `path:line:column: severity: message` line for editors and CI annotations.
+    pub fn render_machine(&self) -> String {
+        match &self.span {
+            Some(span) => format!(
+                "{}:{}:{}: {}: {}",
+                self.path.display(),
+                span.line,
+                span.column,
+                self.severity,
+                self.message
+            ),
+            None => format!(
+                "{}: {}: {}",
+                self.path.display(),
+                self.severity,
+                self.message
+            ),
+        }
+    }
+}
+
+impl CatalogError {
+    /// Converts the error into one located diagnostic per underlying problem.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let error = |path: &Path, span: Option<SourceSpan>, message: String| {
+            Diagnostic::new(Severity::Error, path, span, message)
+        };
+        match self {
+            CatalogError::Io { path, source } => {
+                vec![error(path, None, format!("I/O error: {source}"))]
+            }
+            CatalogError::FrontMatter { path, span, source } => {
+                vec![error(path, Some(span.clone()), source.to_string())]
+            }
+            CatalogError::Config { path, source } => {
+                vec![error(
+                    path,
+                    None,
+                    format!("invalid configuration: {source}"),
+                )]
+            }
+            CatalogError::Template { path, span, source } => {
+                vec![error(path, Some(span.clone()), source.to_string())]
+            }
+            CatalogError::Yaml { path, span, source } => vec![error(
+                path,
+                Some(span.clone()),
+                format!("failed to parse YAML: {}", yaml_message(source)),
+            )],
+            CatalogError::Json { path, source } => {
+                vec![error(
+                    path,
+                    None,
+                    format!("failed to process JSON: {source}"),
+                )]
+            }
+            CatalogError::Duplicate {
+                id,
+                first,
+                duplicate,
+            } => vec![error(
+                duplicate,
+                None,
+                format!(
+                    "duplicate id `{id}` (already defined in {})",
+                    first.display()
+                ),
+            )],
+            CatalogError::UnknownId { kind, id, dir } => {
+                vec![error(dir, None, format!("no {kind} with id `{id}`"))]
+            }
+            CatalogError::MissingParent { path, id, parent } => vec![error(
+                path,
+                None,
+                format!("persona `{id}` extends unknown persona `{parent}`"),
+            )],
+            CatalogError::InheritanceCycle { path, cycle } => vec![error(
+                path,
+                None,
+                format!("inheritance cycle: {}", cycle.join(" -> ")),
+            )],
+            CatalogError::Schema { path, issues } => issues
+                .iter()
+                .map(|issue| error(path, Some(issue.span.clone()), issue.kind.to_string()))
+                .collect(),
+            CatalogError::Structure { path, issues } => issues
+                .iter()
+                .map(|issue| {
+                    error(
+                        issue.path.as_ref().unwrap_or(path),
+                        Some(SourceSpan::line_only(issue.line)),
+                        issue.kind.to_string(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CatalogWarning {
+    pub fn diagnostic(&self) -> Diagnostic {
+        match self {
+            CatalogWarning::MissingField { path, field } => Diagnostic::new(
+                Severity::Warning,
+                path,
+                None,
+                format!("no `{field}` in front matter; catalog listings will omit it"),
+            ),
+            CatalogWarning::DanglingHandoff {
+                path,
+                span,
+                reference,
+            } => Diagnostic::new(
+                Severity::Warning,
+                path,
+                Some(span.clone()),
+                format!("handoff target `{reference}` names no persona"),
+            ),
+            CatalogWarning::UnreachablePersona { path, id } => Diagnostic::new(
+                Severity::Warning,
+                path,
+                None,
+                format!("no persona hands off to `{id}`"),
+            ),
+            CatalogWarning::Overridden { path, id, previous } => Diagnostic::new(
+                Severity::Warning,
+                path,
+                None,
+                format!("overrides `{id}` from {}", previous.display()),
+            ),
+        }
+    }
+}
+
+/// Returns the serde message without the `at line N column M` suffix, which is
+/// relative to the extracted YAML rather than the Markdown file.
+pub(crate) fn yaml_message(err: &serde_yaml_ng::Error) -> String {
+    strip_yaml_location(&err.to_string())
+}
+
+fn strip_yaml_location(message: &str) -> String {
+    const MARKER: &str = " at line ";
+    let mut output = String::new();
+    let mut rest = message;
+    while let Some(start) = rest.find(MARKER) {
+        let after = rest[start + MARKER.len()..].trim_start_matches(|c: char| c.is_ascii_digit());
+        let Some(after) = after.strip_prefix(" column ") else {
+            output.push_str(&rest[..start + MARKER.len()]);
+            rest = &rest[start + MARKER.len()..];
+            continue;
+        };
+        output.push_str(&rest[..start]);
+        rest = after.trim_start_matches(|c: char| c.is_ascii_digit());
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Locates a front matter error in the original file content.
+pub(crate) fn front_matter_span(content: &str, err: &FrontMatterError) -> SourceSpan {
+    if *err == FrontMatterError::Malformed {
+        // A closing delimiter with trailing text is more useful to point at than the opening one.
+        for (index, line) in content.lines().enumerate().skip(1) {
+            let line = line.trim_end_matches('\r');
+            if let Some(rest) = line.strip_prefix("---") {
+                if !rest.trim().is_empty() {
+                    return SourceSpan::at(content, index + 1, 4);
+                }
+                break;
+            }
+        }
+    }
+    SourceSpan::at(content, 1, 1)
+}
+
+/// Maps a YAML error location from the extracted front matter back to the Markdown file.
+///
+/// The front matter starts after the opening `---` line, `parse_front_matter` trims
+/// leading blank lines and indentation, and CRLF normalization never changes line or
+/// column numbers, so only the trimmed prefix needs to be accounted for.
+pub(crate) fn yaml_span(content: &str, err: &serde_yaml_ng::Error) -> SourceSpan {
+    let normalized = content.replace("\r\n", "\n");
+    let source = normalized.strip_prefix('\u{FEFF}').unwrap_or(&normalized);
+    let remainder = source.strip_prefix("---\n").unwrap_or(source);
+    let leading = &remainder[..remainder.len() - remainder.trim_start().len()];
+    let yaml_line = 2 + leading.matches('\n').count();
+    let yaml_column = leading
+        .rsplit('\n')
+        .next()
+        .map_or(0, |tail| tail.chars().count())
+        + 1;
+
+    let (line, column) = match err.location() {
+        Some(location) if location.line() == 1 => (yaml_line, yaml_column + location.column() - 1),
+        Some(location) => (yaml_line + location.line() - 1, location.column()),
+        None => (yaml_line, yaml_column),
+    };
+    SourceSpan::at(content, line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PersonaMeta;
+
+    fn yaml_error(content: &str) -> serde_yaml_ng::Error {
+        let front_matter = crate::parse_front_matter(content).expect("front matter");
+        serde_yaml_ng::from_str::<PersonaMeta>(front_matter.yaml.as_ref()).unwrap_err()
+    }
+
+    #[test]
+    fn maps_yaml_location_to_file_line() {
+        let content = "---\n\nid: one\nname: One\ntags: [a, b\nauthor: x\n---\nbody\n";
+        let span = yaml_span(content, &yaml_error(content));
+        assert_eq!(span.line, 6);
+        assert_eq!(span.snippet.as_deref(), Some("author: x"));
+    }
+
+    #[test]
+    fn maps_yaml_location_with_crlf_line_endings() {
+        let content = "---\r\nid: one\r\nname: [\r\n---\r\nbody\r\n";
+        let span = yaml_span(content, &yaml_error(content));
+        assert_eq!(span.line, 3);
+        assert_eq!(span.snippet.as_deref(), Some("name: ["));
+    }
+
+    #[test]
+    fn maps_yaml_column_on_first_front_matter_line() {
+        let content = "---\nid: @one\nname: One\n---\n";
+        let span = yaml_span(content, &yaml_error(content));
+        assert_eq!((span.line, span.column), (2, 5));
+    }
+
+    #[test]
+    fn points_at_closing_delimiter_with_trailing_text() {
+        let content = "---\nid: one\n--- nope\nbody\n";
+        let span = front_matter_span(content, &FrontMatterError::Malformed);
+        assert_eq!((span.line, span.column), (3, 4));
+        assert_eq!(span.snippet.as_deref(), Some("--- nope"));
+    }
+
+    #[test]
+    fn strips_yaml_relative_location_from_message() {
+        assert_eq!(
+            strip_yaml_location(
+                "did not find expected ',' or ']' at line 4 column 1, while parsing a flow sequence at line 3 column 7"
+            ),
+            "did not find expected ',' or ']', while parsing a flow sequence"
+        );
+        assert_eq!(
+            strip_yaml_location("missing field `name` at line 1 column 1"),
+            "missing field `name`"
+        );
+    }
+
+    #[test]
+    fn renders_human_and_machine_formats() {
+        let diagnostic = Diagnostic {
+            severity: Severity::Error,
+            path: PathBuf::from("personas/ONE.md"),
+            span: Some(SourceSpan::at("---\nid: @one\n", 2, 5)),
+            message: "bad value".into(),
+        };
+        assert_eq!(
+            diagnostic.render_human(),
+            "error: bad value\n --> personas/ONE.md:2:5\n  |\n2 | id: @one\n  |     ^\n"
+        );
+        assert_eq!(
+            diagnostic.render_machine(),
+            "personas/ONE.md:2:5: error: bad value"
+        );
+    }
+}
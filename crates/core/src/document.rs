@@ -0,0 +1,219 @@
+use crate::{Heading, parse_headings};
+
+/// A persona or scenario body split into its title and level-two sections.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Document {
+    /// The first level-one heading, if any.
+    pub title: Option<Heading>,
+    /// The raw Markdown before the first level-two section, without the title line.
+    pub preamble: String,
+    /// Every level-two section, in document order.
+    pub sections: Vec<Section>,
+    /// Every ATX heading outside code fences, in document order.
+    pub headings: Vec<Heading>,
+    /// 1-based file line of the last line of the body.
+    pub end_line: usize,
+}
+
+/// A `## ` heading together with everything up to the next level-one or level-two heading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub heading: Heading,
+    /// The raw Markdown below the heading, including any `###` subsections.
+    pub content: String,
+    /// Top-level list items in `content`, outside code fences.
+    pub items: Vec<ListItem>,
+}
+
+/// A top-level `-`, `*`, `+`, or numbered list item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListItem {
+    /// 1-based file line on which the item starts.
+    pub line: usize,
+    /// The item text without its marker, with continuation lines joined by spaces.
+    /// Nested list items are not included.
+    pub text: String,
+}
+
+impl Document {
+    /// Parses `body`, whose first line is line `first_line` of the source file.
+    pub fn parse(body: &str, first_line: usize) -> Self {
+        let headings = parse_headings(body, first_line);
+        let lines = body.lines().collect::<Vec<_>>();
+        let end_line = first_line + lines.len().saturating_sub(1);
+
+        let sections = headings
+            .iter()
+            .enumerate()
+            .filter(|(_, heading)| heading.level == 2)
+            .map(|(index, heading)| {
+                let start = heading.line + 1 - first_line;
+                let end = headings[index + 1..]
+                    .iter()
+                    .find(|next| next.level <= 2)
+                    .map_or(lines.len(), |next| next.line - first_line);
+                let content = &lines[start.min(end)..end];
+                Section {
+                    heading: heading.clone(),
+                    content: content.iter().map(|line| format!("{line}\n")).collect(),
+                    items: parse_items(content, heading.line + 1),
+                }
+            })
+            .collect();
+
+        let title = headings.iter().find(|heading| heading.level == 1).cloned();
+        let preamble_end = headings
+            .iter()
+            .find(|heading| heading.level == 2)
+            .map_or(lines.len(), |heading| heading.line - first_line);
+        let preamble = lines[..preamble_end]
+            .iter()
+            .enumerate()
+            .filter(|(offset, _)| {
+                title
+                    .as_ref()
+                    .is_none_or(|title| title.line != first_line + offset)
+            })
+            .map(|(_, line)| format!("{line}\n"))
+            .collect();
+
+        Self {
+            title,
+            preamble,
+            sections,
+            headings,
+            end_line,
+        }
+    }
+
+    /// Returns the first level-two section titled `name`.
+    pub fn section(&self, name: &str) -> Option<&Section> {
+        self.sections
+            .iter()
+            .find(|section| section.heading.text == name)
+    }
+}
+
+impl Section {
+    /// Returns the text of every top-level list item, e.g. each Responsibilities Checklist entry.
+    pub fn bullets(&self) -> Vec<String> {
+        self.items.iter().map(|item| item.text.clone()).collect()
+    }
+
+    /// Whether the section holds nothing but blank lines.
+    pub fn is_empty(&self) -> bool {
+        self.content.trim().is_empty()
+    }
+}
+
+fn parse_items(lines: &[&str], first_line: usize) -> Vec<ListItem> {
+    let mut items = Vec::new();
+    let mut current: Option<ListItem> = None;
+    let mut fence: Option<&str> = None;
+    for (offset, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"]
+            .into_iter()
+            .find(|marker| trimmed.starts_with(marker))
+        {
+            items.extend(current.take());
+            fence = Some(marker);
+            continue;
+        }
+        if trimmed.is_empty() {
+            items.extend(current.take());
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        match list_marker(trimmed) {
+            Some(text) if indent < 2 => {
+                items.extend(current.take());
+                current = Some(ListItem {
+                    line: first_line + offset,
+                    text: text.trim().to_string(),
+                });
+            }
+            // Nested list items belong to their parent but are not part of its text.
+            Some(_) => {}
+            None => {
+                if let Some(item) = current.as_mut() {
+                    item.text.push(' ');
+                    item.text.push_str(trimmed.trim_end());
+                }
+            }
+        }
+    }
+    items.extend(current);
+    items
+}
+
+/// Strips a bullet (`-`, `*`, `+`) or ordered (`1.`, `1)`) list marker.
+fn list_marker(line: &str) -> Option<&str> {
+    if let Some(rest) = line
+        .strip_prefix(['-', '*', '+'])
+        .filter(|rest| rest.starts_with([' ', '\t']))
+    {
+        return Some(rest);
+    }
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits == 0 {
+        return None;
+    }
+    line[digits..]
+        .strip_prefix(['.', ')'])
+        .filter(|rest| rest.starts_with([' ', '\t']))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_sections_and_list_items() {
+        let body = "# Delivery Engineer\n\nIntro.\n\n## Required Artifacts\n- Tests and\n  docs.\n  - nested\n* Release notes\n\n```\n- not an item\n```\n### Details\n1. First\n2) Second\n\n## Collaboration Signals\n\n";
+        let document = Document::parse(body, 5);
+
+        assert_eq!(
+            document.title.as_ref().map(|title| title.text.as_str()),
+            Some("Delivery Engineer")
+        );
+        assert_eq!(document.preamble, "\nIntro.\n\n");
+        assert_eq!(
+            document
+                .sections
+                .iter()
+                .map(|section| section.heading.text.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Required Artifacts", "Collaboration Signals"]
+        );
+
+        let artifacts = document.section("Required Artifacts").expect("section");
+        assert_eq!(
+            artifacts.bullets(),
+            vec!["Tests and docs.", "Release notes", "First", "Second"]
+        );
+        assert_eq!(
+            artifacts
+                .items
+                .iter()
+                .map(|item| item.line)
+                .collect::<Vec<_>>(),
+            vec![10, 13, 19, 20]
+        );
+        assert!(artifacts.content.contains("### Details\n"));
+        assert!(
+            document
+                .section("Collaboration Signals")
+                .expect("section")
+                .is_empty()
+        );
+        assert_eq!(document.end_line, 23);
+        assert!(document.section("Missing").is_none());
+    }
+}
@@ -0,0 +1,362 @@
+use crate::CatalogError;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Values longer than this are shortened when a change is displayed.
+const MAX_DISPLAY_WIDTH: usize = 72;
+
+/// How a checked-in catalog differs from the one its sources would generate.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CatalogDrift {
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub drift: Drift,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum Drift {
+    /// The catalog file does not exist.
+    Missing,
+    /// The catalog file is not valid JSON.
+    Invalid { message: String },
+    /// The catalog parses but its contents differ.
+    Changed { changes: Vec<JsonChange> },
+}
+
+/// One difference between two JSON documents, addressed by a path such as
+/// `personas[id=one].tags[0]`. Array elements that all carry a unique string `id`
+/// are matched by id, so inserting a persona reports one addition rather than a
+/// change to every later entry.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum JsonChange {
+    Added {
+        path: String,
+        value: Value,
+    },
+    Removed {
+        path: String,
+        value: Value,
+    },
+    Changed {
+        path: String,
+        old: Value,
+        new: Value,
+    },
+    /// The same elements appear in a different order.
+    Reordered {
+        path: String,
+    },
+}
+
+/// Compares the catalog checked in at `dir/catalog.json` with `index`.
+///
+/// Formatting, key order, and line endings inside strings are ignored; only a
+/// difference in the JSON values counts as drift.
+pub fn catalog_drift<T: Serialize>(
+    dir: &Path,
+    index: &T,
+) -> Result<Option<CatalogDrift>, CatalogError> {
+    let path = dir.join("catalog.json");
+    let expected =
+        serde_json::to_value(index).map_err(|source| CatalogError::json(&path, source))?;
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(source) if source.kind() == io::ErrorKind::NotFound => {
+            return Ok(Some(CatalogDrift {
+                path,
+                drift: Drift::Missing,
+            }));
+        }
+        Err(source) => return Err(CatalogError::io(&path, source)),
+    };
+    let current = match serde_json::from_str::<Value>(&contents) {
+        Ok(current) => current,
+        Err(err) => {
+            return Ok(Some(CatalogDrift {
+                path,
+                drift: Drift::Invalid {
+                    message: err.to_string(),
+                },
+            }));
+        }
+    };
+
+    let changes = diff_json(&normalize(current), &normalize(expected));
+    Ok((!changes.is_empty()).then_some(CatalogDrift {
+        path,
+        drift: Drift::Changed { changes },
+    }))
+}
+
+/// Lists the changes that turn `old` into `new`, in document order.
+pub fn diff_json(old: &Value, new: &Value) -> Vec<JsonChange> {
+    let mut changes = Vec::new();
+    diff_value(String::new(), old, new, &mut changes);
+    changes
+}
+
+fn diff_value(path: String, old: &Value, new: &Value, changes: &mut Vec<JsonChange>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old {
+                let child = join_key(&path, key);
+                match new.get(key) {
+                    Some(new_value) => diff_value(child, old_value, new_value, changes),
+                    None => changes.push(JsonChange::Removed {
+                        path: child,
+                        value: old_value.clone(),
+                    }),
+                }
+            }
+            for (key, new_value) in new {
+                if !old.contains_key(key) {
+                    changes.push(JsonChange::Added {
+                        path: join_key(&path, key),
+                        value: new_value.clone(),
+                    });
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => match (ids(old), ids(new)) {
+            (Some(old_ids), Some(new_ids)) => {
+                diff_by_id(&path, old, &old_ids, new, &new_ids, changes)
+            }
+            _ => diff_by_index(&path, old, new, changes),
+        },
+        _ if old != new => changes.push(JsonChange::Changed {
+            path,
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+fn diff_by_index(path: &str, old: &[Value], new: &[Value], changes: &mut Vec<JsonChange>) {
+    for (index, (old_value, new_value)) in old.iter().zip(new).enumerate() {
+        diff_value(format!("{path}[{index}]"), old_value, new_value, changes);
+    }
+    for (index, value) in old.iter().enumerate().skip(new.len()) {
+        changes.push(JsonChange::Removed {
+            path: format!("{path}[{index}]"),
+            value: value.clone(),
+        });
+    }
+    for (index, value) in new.iter().enumerate().skip(old.len()) {
+        changes.push(JsonChange::Added {
+            path: format!("{path}[{index}]"),
+            value: value.clone(),
+        });
+    }
+}
+
+fn diff_by_id(
+    path: &str,
+    old: &[Value],
+    old_ids: &[&str],
+    new: &[Value],
+    new_ids: &[&str],
+    changes: &mut Vec<JsonChange>,
+) {
+    let element = |id: &str| format!("{path}[id={id}]");
+    for (old_value, id) in old.iter().zip(old_ids) {
+        match new_ids.iter().position(|new_id| new_id == id) {
+            Some(position) => diff_value(element(id), old_value, &new[position], changes),
+            None => changes.push(JsonChange::Removed {
+                path: element(id),
+                value: old_value.clone(),
+            }),
+        }
+    }
+    for (new_value, id) in new.iter().zip(new_ids) {
+        if !old_ids.contains(id) {
+            changes.push(JsonChange::Added {
+                path: element(id),
+                value: new_value.clone(),
+            });
+        }
+    }
+
+    let kept_in_old_order = old_ids.iter().filter(|id| new_ids.contains(id));
+    let kept_in_new_order = new_ids.iter().filter(|id| old_ids.contains(id));
+    if !kept_in_old_order.eq(kept_in_new_order) {
+        changes.push(JsonChange::Reordered {
+            path: path.to_string(),
+        });
+    }
+}
+
+/// The `id` of every element when all elements are objects with distinct string ids.
+fn ids(values: &[Value]) -> Option<Vec<&str>> {
+    let ids = values
+        .iter()
+        .map(|value| value.get("id").and_then(Value::as_str))
+        .collect::<Option<Vec<_>>>()?;
+    let distinct = ids
+        .iter()
+        .enumerate()
+        .all(|(index, id)| !ids[..index].contains(id));
+    (!ids.is_empty() && distinct).then_some(ids)
+}
+
+fn join_key(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+/// Replaces `\r\n` with `\n` in every string so CRLF checkouts compare equal.
+fn normalize(value: Value) -> Value {
+    match value {
+        Value::String(text) => Value::String(text.replace("\r\n", "\n")),
+        Value::Array(values) => Value::Array(values.into_iter().map(normalize).collect()),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (key, normalize(value)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+impl fmt::Display for CatalogDrift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path.display();
+        match &self.drift {
+            Drift::Missing => write!(f, "{path} is missing"),
+            Drift::Invalid { message } => write!(f, "{path} is not valid JSON: {message}"),
+            Drift::Changed { changes } => {
+                write!(f, "{path} differs from its sources:")?;
+                for change in changes {
+                    write!(f, "\n  {change}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for JsonChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added { path, value } => write!(f, "+ {}: {}", label(path), short(value)),
+            Self::Removed { path, value } => write!(f, "- {}: {}", label(path), short(value)),
+            Self::Changed { path, old, new } => {
+                write!(f, "~ {}: {} → {}", label(path), short(old), short(new))
+            }
+            Self::Reordered { path } => write!(f, "~ {}: entries reordered", label(path)),
+        }
+    }
+}
+
+fn label(path: &str) -> &str {
+    if path.is_empty() { "(root)" } else { path }
+}
+
+fn short(value: &Value) -> String {
+    let text = value.to_string();
+    match text.char_indices().nth(MAX_DISPLAY_WIDTH) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn matches_array_elements_by_id() {
+        let old = json!({
+            "base_uri": "personas",
+            "personas": [
+                {"id": "a", "name": "A", "tags": ["x"]},
+                {"id": "c", "name": "C"},
+            ],
+        });
+        let new = json!({
+            "base_uri": "personas",
+            "personas": [
+                {"id": "a", "name": "A", "tags": ["x", "y"]},
+                {"id": "b", "name": "B"},
+                {"id": "c", "name": "Sea"},
+            ],
+        });
+
+        let rendered = diff_json(&old, &new)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rendered,
+            [
+                "+ personas[id=a].tags[1]: \"y\"",
+                "~ personas[id=c].name: \"C\" → \"Sea\"",
+                "+ personas[id=b]: {\"id\":\"b\",\"name\":\"B\"}",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_reordering_and_removed_keys() {
+        let old = json!({"items": [{"id": "a"}, {"id": "b"}], "legacy": true});
+        let new = json!({"items": [{"id": "b"}, {"id": "a"}]});
+        assert_eq!(
+            diff_json(&old, &new),
+            vec![
+                JsonChange::Reordered {
+                    path: "items".into()
+                },
+                JsonChange::Removed {
+                    path: "legacy".into(),
+                    value: json!(true)
+                },
+            ]
+        );
+        assert_eq!(
+            JsonChange::Changed {
+                path: String::new(),
+                old: json!("x".repeat(100)),
+                new: json!(null)
+            }
+            .to_string(),
+            format!("~ (root): \"{}… → null", "x".repeat(71))
+        );
+    }
+
+    #[test]
+    fn catalog_drift_ignores_formatting_and_line_endings() {
+        let tmp = tempdir().expect("tempdir");
+        let index = json!({"base_uri": "personas", "personas": [{"id": "a", "body": "one\ntwo"}]});
+        assert_eq!(
+            catalog_drift(tmp.path(), &index).expect("drift"),
+            Some(CatalogDrift {
+                path: tmp.path().join("catalog.json"),
+                drift: Drift::Missing
+            })
+        );
+
+        fs::write(
+            tmp.path().join("catalog.json"),
+            "{\r\n\"personas\": [{\"body\": \"one\\r\\ntwo\", \"id\": \"a\"}],\r\n\"base_uri\": \"personas\"}",
+        )
+        .expect("catalog");
+        assert_eq!(catalog_drift(tmp.path(), &index).expect("drift"), None);
+
+        fs::write(tmp.path().join("catalog.json"), "<<<<<<< HEAD").expect("catalog");
+        let drift = catalog_drift(tmp.path(), &index)
+            .expect("drift")
+            .expect("invalid");
+        assert!(matches!(drift.drift, Drift::Invalid { .. }));
+    }
+}
@@ -0,0 +1,409 @@
+use crate::{CatalogWarning, Document, PersonaEntry, PersonaSource, SourceSpan};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// The persona section whose bullets name the next persona to engage (SPECIFICATION §3.4).
+pub const HANDOFF_SECTION: &str = "When to Switch Away";
+
+/// A directed edge from one persona to another, taken from a `When to Switch Away` bullet.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Handoff {
+    /// Id of the persona to switch to.
+    pub to: String,
+    /// The condition written before the arrow.
+    pub trigger: String,
+}
+
+/// A `When to Switch Away` bullet of the form `trigger → target`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandoffBullet {
+    pub trigger: String,
+    pub target: String,
+    /// Where `target` starts in the source file.
+    pub span: SourceSpan,
+}
+
+/// Extracts every arrow bullet from the `## When to Switch Away` section of `body`.
+///
+/// Both `→` and `->` are accepted; bullets without an arrow are ignored.
+pub fn parse_handoff_bullets(body: &str, first_line: usize) -> Vec<HandoffBullet> {
+    let document = Document::parse(body, first_line);
+    let Some(section) = document.section(HANDOFF_SECTION) else {
+        return Vec::new();
+    };
+    let lines = body.lines().collect::<Vec<_>>();
+    section
+        .items
+        .iter()
+        .filter_map(|item| {
+            let (trigger, target) = split_arrow(&item.text)?;
+            let text = lines[item.line - first_line];
+            let column = split_arrow(text).map_or(1, |(_, target)| {
+                text[..text.len() - target.trim_start().len()]
+                    .chars()
+                    .count()
+                    + 1
+            });
+            Some(HandoffBullet {
+                trigger: trigger.trim().to_string(),
+                target: target.trim().trim_end_matches('.').to_string(),
+                span: SourceSpan {
+                    line: item.line,
+                    column,
+                    snippet: Some(text.trim_end().to_string()),
+                },
+            })
+        })
+        .collect()
+}
+
+fn split_arrow(text: &str) -> Option<(&str, &str)> {
+    text.split_once('→').or_else(|| text.split_once("->"))
+}
+
+/// Resolves every persona's handoff bullets against the other personas' names and ids.
+///
+/// Fills in `entry.handoffs` for each source and returns warnings for bullets that name
+/// no known persona and for personas that no other persona hands off to.
+pub fn resolve_handoffs(sources: &mut [PersonaSource]) -> Vec<CatalogWarning> {
+    let mut needles = sources
+        .iter()
+        .flat_map(|source| {
+            let meta = &source.entry.meta;
+            [
+                (meta.name.to_lowercase(), meta.id.clone()),
+                (meta.id.to_lowercase(), meta.id.clone()),
+            ]
+        })
+        .collect::<Vec<_>>();
+    // Longer names win so `Reliability & Security Engineer` is never read as a shorter match.
+    needles.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+    needles.dedup();
+
+    let mut warnings = Vec::new();
+    let mut targeted = HashSet::new();
+    for source in sources.iter_mut() {
+        let own_id = source.entry.meta.id.clone();
+        let mut handoffs = Vec::new();
+        for bullet in parse_handoff_bullets(&source.body, source.body_line) {
+            let targets = match_personas(&bullet.target, &needles);
+            if targets.is_empty() {
+                let (path, line) = source.locate(bullet.span.line);
+                warnings.push(CatalogWarning::DanglingHandoff {
+                    path: path.to_path_buf(),
+                    span: SourceSpan {
+                        line,
+                        ..bullet.span
+                    },
+                    reference: bullet.target,
+                });
+                continue;
+            }
+            for to in targets.into_iter().filter(|to| *to != own_id) {
+                targeted.insert(to.clone());
+                handoffs.push(Handoff {
+                    to,
+                    trigger: bullet.trigger.clone(),
+                });
+            }
+        }
+        source.entry.handoffs = handoffs;
+    }
+
+    // A catalog without any handoffs has no graph to be unreachable from.
+    if !targeted.is_empty() {
+        for source in sources.iter() {
+            if !targeted.contains(&source.entry.meta.id) {
+                warnings.push(CatalogWarning::UnreachablePersona {
+                    path: source.path.clone(),
+                    id: source.entry.meta.id.clone(),
+                });
+            }
+        }
+    }
+    warnings
+}
+
+/// Renders the handoff graph of `entries` as a Mermaid flowchart, labelling edges with triggers.
+///
+/// Mermaid node ids must be plain words, so nodes are numbered `n0`, `n1`, … in id order;
+/// deriving them from persona ids would merge ids such as `a-b` and `a_b`.
+pub fn render_handoff_mermaid(entries: &[PersonaEntry]) -> String {
+    let entries = sorted_by_id(entries);
+    let nodes = entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| (entry.meta.id.as_str(), format!("n{index}")))
+        .collect::<HashMap<_, _>>();
+    let mut output = String::from("flowchart LR\n");
+    for entry in &entries {
+        output.push_str(&format!(
+            "    {}[\"{}\"]\n",
+            nodes[entry.meta.id.as_str()],
+            mermaid_text(&entry.meta.name)
+        ));
+    }
+    for entry in &entries {
+        for handoff in &entry.handoffs {
+            // Handoffs always target a listed persona; skip any that does not.
+            let Some(to) = nodes.get(handoff.to.as_str()) else {
+                continue;
+            };
+            output.push_str(&format!(
+                "    {} -->|\"{}\"| {to}\n",
+                nodes[entry.meta.id.as_str()],
+                mermaid_text(&handoff.trigger),
+            ));
+        }
+    }
+    output
+}
+
+/// Renders `docs/PERSONA_HANDOFFS.md`: an explanatory header followed by the Mermaid flowchart.
+pub fn render_handoff_document(entries: &[PersonaEntry]) -> String {
+    let mut output = String::new();
+    output.push_str("# Persona Handoffs\n\n");
+    output.push_str("This file is auto-generated by `cargo run -p personas-core --bin generate-handoff-graph`.\n");
+    output.push_str(
+        "Do not edit it manually; instead, update the `## When to Switch Away` bullets in the personas and rerun the generator.\n\n",
+    );
+    output.push_str("Each arrow points to the persona to engage next and is labelled with the trigger that calls for the switch. A Graphviz rendering of the same graph lives in `PERSONA_HANDOFFS.dot`.\n\n");
+    output.push_str("```mermaid\n");
+    output.push_str(&render_handoff_mermaid(entries));
+    output.push_str("```\n");
+    output
+}
+
+/// Renders the handoff graph of `entries` as a Graphviz DOT digraph, labelling edges with triggers.
+pub fn render_handoff_dot(entries: &[PersonaEntry]) -> String {
+    let entries = sorted_by_id(entries);
+    let mut output = String::from("digraph handoffs {\n    rankdir=LR;\n    node [shape=box];\n");
+    for entry in &entries {
+        output.push_str(&format!(
+            "    \"{}\" [label=\"{}\"];\n",
+            dot_text(&entry.meta.id),
+            dot_text(&entry.meta.name)
+        ));
+    }
+    for entry in &entries {
+        for handoff in &entry.handoffs {
+            output.push_str(&format!(
+                "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                dot_text(&entry.meta.id),
+                dot_text(&handoff.to),
+                dot_text(&handoff.trigger)
+            ));
+        }
+    }
+    output.push_str("}\n");
+    output
+}
+
+fn sorted_by_id(entries: &[PersonaEntry]) -> Vec<&PersonaEntry> {
+    let mut entries = entries.iter().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.meta.id.cmp(&b.meta.id));
+    entries
+}
+
+fn mermaid_text(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+fn dot_text(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Returns the ids named in `target`, in the order they appear.
+fn match_personas(target: &str, needles: &[(String, String)]) -> Vec<String> {
+    let haystack = target.to_lowercase();
+    let mut claimed = vec![false; haystack.len()];
+    let mut found = Vec::new();
+    for (needle, id) in needles {
+        for (start, _) in haystack.match_indices(needle.as_str()) {
+            let end = start + needle.len();
+            if claimed[start..end].iter().any(|taken| *taken) || !is_boundary(&haystack, start, end)
+            {
+                continue;
+            }
+            claimed[start..end].fill(true);
+            found.push((start, id.clone()));
+        }
+    }
+    found.sort();
+    let mut ids = Vec::new();
+    for (_, id) in found {
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    ids
+}
+
+fn is_boundary(text: &str, start: usize, end: usize) -> bool {
+    let word = |c: char| c.is_alphanumeric() || c == '_';
+    !text[..start].chars().next_back().is_some_and(word)
+        && !text[end..].chars().next().is_some_and(word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PersonaEntry, PersonaMeta};
+    use std::path::PathBuf;
+
+    fn source(id: &str, name: &str, body: &str) -> PersonaSource {
+        PersonaSource {
+            entry: PersonaEntry {
+                meta: PersonaMeta {
+                    id: id.into(),
+                    name: name.into(),
+                    description: None,
+                    tags: None,
+                    author: None,
+                    created_at: None,
+                    version: None,
+                    extends: None,
+                    extra: Default::default(),
+                },
+                uri: format!("https://example.invalid/{id}.md"),
+                root: "personas".into(),
+                handoffs: Vec::new(),
+            },
+            path: PathBuf::from(format!("personas/{id}.md")),
+            body: body.into(),
+            body_line: 5,
+            origins: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn parses_arrow_bullets_in_section_only() {
+        let body = "# A\n\n## When to Switch Away\n- Scope drifts → engage Analyst.\n- Plain note\n* Tests -> `qa`\n\n```\n- x → y\n```\n\n## Required Artifacts\n- Done → B\n";
+        let bullets = parse_handoff_bullets(body, 5);
+        assert_eq!(
+            bullets
+                .iter()
+                .map(|bullet| (
+                    bullet.span.line,
+                    bullet.trigger.as_str(),
+                    bullet.target.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            vec![(8, "Scope drifts", "engage Analyst"), (10, "Tests", "`qa`"),]
+        );
+        assert_eq!(bullets[0].span.column, 18);
+    }
+
+    #[test]
+    fn resolves_names_and_ids_and_reports_gaps() {
+        let mut sources = vec![
+            source(
+                "analyst",
+                "Analyst",
+                "## When to Switch Away\n- Build time → escalate to Reliability & Security Engineer or `qa`.\n- Unknown → ask the Oracle.\n",
+            ),
+            source(
+                "qa",
+                "Quality Engineer",
+                "## When to Switch Away\n- Always → Security Engineer\n",
+            ),
+            source(
+                "reliability",
+                "Reliability & Security Engineer",
+                "## When to Switch Away\n- Triage → back to analyst\n",
+            ),
+            source("security", "Security Engineer", ""),
+        ];
+
+        let warnings = resolve_handoffs(&mut sources);
+
+        let targets = |index: usize| {
+            sources[index]
+                .entry
+                .handoffs
+                .iter()
+                .map(|handoff| handoff.to.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(targets(0), vec!["reliability", "qa"]);
+        assert_eq!(sources[0].entry.handoffs[0].trigger, "Build time");
+        assert_eq!(targets(1), vec!["security"]);
+        assert_eq!(targets(2), vec!["analyst"]);
+        assert_eq!(
+            warnings.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "personas/analyst.md:7 hands off to `ask the Oracle`, which names no persona"
+                    .to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_personas_nobody_hands_off_to() {
+        let mut sources = vec![
+            source("a", "Alpha", "## When to Switch Away\n- Done → Beta\n"),
+            source("b", "Beta", ""),
+        ];
+        let warnings = resolve_handoffs(&mut sources);
+        assert_eq!(
+            warnings,
+            vec![CatalogWarning::UnreachablePersona {
+                path: PathBuf::from("personas/a.md"),
+                id: "a".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn renders_mermaid_and_dot() {
+        let mut sources = vec![
+            source(
+                "b_two",
+                "Beta \"Two\"",
+                "## When to Switch Away\n- Scope drifts → Alpha\n",
+            ),
+            source(
+                "a",
+                "Alpha",
+                "## When to Switch Away\n- Ready → Beta \"Two\"\n",
+            ),
+        ];
+        resolve_handoffs(&mut sources);
+        let entries = sources
+            .into_iter()
+            .map(|source| source.entry)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            render_handoff_mermaid(&entries),
+            "flowchart LR\n    n0[\"Alpha\"]\n    n1[\"Beta #quot;Two#quot;\"]\n    n0 -->|\"Ready\"| n1\n    n1 -->|\"Scope drifts\"| n0\n"
+        );
+        assert_eq!(
+            render_handoff_dot(&entries),
+            "digraph handoffs {\n    rankdir=LR;\n    node [shape=box];\n    \"a\" [label=\"Alpha\"];\n    \"b_two\" [label=\"Beta \\\"Two\\\"\"];\n    \"a\" -> \"b_two\" [label=\"Ready\"];\n    \"b_two\" -> \"a\" [label=\"Scope drifts\"];\n}\n"
+        );
+    }
+
+    #[test]
+    fn mermaid_nodes_stay_distinct_for_similar_ids() {
+        let mut sources = vec![
+            source("a-b", "Dash", "## When to Switch Away\n- Done → `a_b`\n"),
+            source(
+                "a_b",
+                "Underscore",
+                "## When to Switch Away\n- Done → `a.b`\n",
+            ),
+            source("a.b", "Dot", "## When to Switch Away\n- Done → `a-b`\n"),
+        ];
+        resolve_handoffs(&mut sources);
+        let entries = sources
+            .into_iter()
+            .map(|source| source.entry)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            render_handoff_mermaid(&entries),
+            "flowchart LR\n    n0[\"Dash\"]\n    n1[\"Dot\"]\n    n2[\"Underscore\"]\n    n0 -->|\"Done\"| n2\n    n1 -->|\"Done\"| n0\n    n2 -->|\"Done\"| n1\n"
+        );
+    }
+}
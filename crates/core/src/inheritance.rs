@@ -0,0 +1,393 @@
+use crate::{CatalogError, PersonaMeta, PersonaSource};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Replaces every persona that `extends` another with its merged form.
+///
+/// Personas whose chain ends in an unknown id or loops back on itself are
+/// dropped and reported once per broken link, not once per descendant.
+pub(crate) fn resolve_inheritance(
+    sources: Vec<PersonaSource>,
+) -> (Vec<PersonaSource>, Vec<CatalogError>) {
+    let by_id = sources
+        .iter()
+        .enumerate()
+        .map(|(index, source)| (source.entry.meta.id.as_str(), index))
+        .collect::<HashMap<_, _>>();
+
+    let mut errors = Vec::new();
+    let mut reported = HashSet::new();
+    let mut chains = Vec::with_capacity(sources.len());
+    for index in 0..sources.len() {
+        match parent_chain(&sources, &by_id, index) {
+            Ok(chain) => chains.push(Some(chain)),
+            Err(broken) => {
+                if let Some(error) = broken.into_error(&sources, &mut reported) {
+                    errors.push(error);
+                }
+                chains.push(None);
+            }
+        }
+    }
+
+    let resolved = chains
+        .iter()
+        .enumerate()
+        .filter_map(|(index, chain)| {
+            let chain = chain.as_ref()?;
+            let source = &sources[index];
+            if chain.is_empty() {
+                return Some(source.clone());
+            }
+            // Fold from the root ancestor down to the persona itself.
+            let root = sources[*chain.last()?].clone();
+            let merged = chain[..chain.len() - 1]
+                .iter()
+                .rev()
+                .map(|&ancestor| &sources[ancestor])
+                .chain([source])
+                .fold(root, |parent, child| merge(&parent, child));
+            Some(merged)
+        })
+        .collect();
+    (resolved, errors)
+}
+
+/// Why a persona's ancestors could not be resolved.
+enum BrokenChain {
+    MissingParent { child: usize, parent: String },
+    Cycle { members: Vec<usize> },
+}
+
+impl BrokenChain {
+    fn into_error(
+        self,
+        sources: &[PersonaSource],
+        reported: &mut HashSet<Vec<usize>>,
+    ) -> Option<CatalogError> {
+        match self {
+            BrokenChain::MissingParent { child, parent } => {
+                if !reported.insert(vec![child]) {
+                    return None;
+                }
+                let source = &sources[child];
+                Some(CatalogError::missing_parent(
+                    &source.path,
+                    &source.entry.meta.id,
+                    &parent,
+                ))
+            }
+            BrokenChain::Cycle { members } => {
+                // Start the cycle at the member whose file sorts first so every
+                // entry point reports the same loop.
+                let start = (0..members.len())
+                    .min_by(|&a, &b| sources[members[a]].path.cmp(&sources[members[b]].path))?;
+                let mut ordered = members[start..].to_vec();
+                ordered.extend_from_slice(&members[..start]);
+
+                let mut key = ordered.clone();
+                key.sort_unstable();
+                if !reported.insert(key) {
+                    return None;
+                }
+                let mut cycle = ordered
+                    .iter()
+                    .map(|&member| sources[member].entry.meta.id.clone())
+                    .collect::<Vec<_>>();
+                cycle.push(cycle[0].clone());
+                Some(CatalogError::inheritance_cycle(
+                    &sources[ordered[0]].path,
+                    cycle,
+                ))
+            }
+        }
+    }
+}
+
+/// Returns the ancestors of `index`, nearest first.
+fn parent_chain(
+    sources: &[PersonaSource],
+    by_id: &HashMap<&str, usize>,
+    index: usize,
+) -> Result<Vec<usize>, BrokenChain> {
+    let mut visited = vec![index];
+    let mut current = index;
+    while let Some(parent) = sources[current].entry.meta.extends.as_deref() {
+        let Some(&next) = by_id.get(parent) else {
+            return Err(BrokenChain::MissingParent {
+                child: current,
+                parent: parent.to_string(),
+            });
+        };
+        if let Some(position) = visited.iter().position(|&seen| seen == next) {
+            return Err(BrokenChain::Cycle {
+                members: visited[position..].to_vec(),
+            });
+        }
+        visited.push(next);
+        current = next;
+    }
+    visited.remove(0);
+    Ok(visited)
+}
+
+/// Fields the child sets win; unset fields and custom keys fall back to the parent's.
+fn merge_meta(parent: PersonaMeta, child: &PersonaMeta) -> PersonaMeta {
+    let mut extra = parent.extra;
+    extra.extend(child.extra.clone());
+    PersonaMeta {
+        id: child.id.clone(),
+        name: child.name.clone(),
+        description: child.description.clone().or(parent.description),
+        tags: child.tags.clone().or(parent.tags),
+        author: child.author.clone().or(parent.author),
+        created_at: child.created_at.or(parent.created_at),
+        version: child.version.or(parent.version),
+        extends: child.extends.clone(),
+        extra,
+    }
+}
+
+/// `child` with the metadata and body of its (already merged) `parent` overlaid.
+fn merge(parent: &PersonaSource, child: &PersonaSource) -> PersonaSource {
+    let (body, origins) = merge_body(parent, child);
+    let mut merged = child.clone();
+    merged.entry.meta = merge_meta(parent.entry.meta.clone(), &child.entry.meta);
+    merged.body = body;
+    merged.origins = origins;
+    merged
+}
+
+/// A line of a merged body and the file line it was written on.
+type Line<'a> = (String, (&'a Path, usize));
+
+/// Overlays `child` on `parent` section by section, returning the body and the file
+/// line each of its lines came from.
+///
+/// A child section replaces the parent section with the same heading in place;
+/// any other child section is appended after the parent's. The child's preamble
+/// replaces the parent's unless it is blank.
+fn merge_body(parent: &PersonaSource, child: &PersonaSource) -> (String, Vec<(PathBuf, usize)>) {
+    let parent_document = parent.document();
+    let child_document = child.document();
+
+    let title = match &child_document.title {
+        Some(title) => (format!("# {}", title.text), child.locate(title.line)),
+        None => (
+            format!("# {}", child.entry.meta.name),
+            (child.path.as_path(), child.body_line),
+        ),
+    };
+    let mut blocks = vec![vec![title]];
+    let (owner, document) = if child_document.preamble.trim().is_empty() {
+        (parent, &parent_document)
+    } else {
+        (child, &child_document)
+    };
+    let title_line = document.title.as_ref().map(|title| title.line);
+    let numbers = (owner.body_line..).filter(|line| Some(*line) != title_line);
+    blocks.push(located_lines(owner, &document.preamble, numbers));
+
+    let inherited = parent_document.sections.iter().map(|section| {
+        match child_document.section(&section.heading.text) {
+            Some(replacement) => (child, replacement),
+            None => (parent, section),
+        }
+    });
+    let added = child_document
+        .sections
+        .iter()
+        .filter(|section| parent_document.section(&section.heading.text).is_none())
+        .map(|section| (child, section));
+    for (owner, section) in inherited.chain(added) {
+        let heading = section.heading.line;
+        let mut block = vec![(
+            format!("## {}", section.heading.text),
+            owner.locate(heading),
+        )];
+        block.extend(located_lines(owner, &section.content, heading + 1..));
+        blocks.push(block);
+    }
+
+    let mut body = String::new();
+    let mut origins = Vec::new();
+    for block in blocks.into_iter().filter(|block| !block.is_empty()) {
+        // The blank line between blocks is attributed to the line above it.
+        if let Some((path, line)) = origins.last().cloned() {
+            body.push('\n');
+            origins.push((path, line + 1));
+        }
+        for (text, (path, line)) in block {
+            body.push_str(&text);
+            body.push('\n');
+            origins.push((path.to_path_buf(), line));
+        }
+    }
+    (body, origins)
+}
+
+/// The lines of `text`, numbered by `numbers` within `owner`'s body, without leading
+/// or trailing blank lines.
+fn located_lines<'a>(
+    owner: &'a PersonaSource,
+    text: &str,
+    numbers: impl Iterator<Item = usize>,
+) -> Vec<Line<'a>> {
+    let mut lines = text
+        .lines()
+        .zip(numbers)
+        .map(|(text, line)| (text.to_string(), owner.locate(line)))
+        .collect::<Vec<_>>();
+    while lines.last().is_some_and(|(text, _)| text.is_empty()) {
+        lines.pop();
+    }
+    let start = lines
+        .iter()
+        .position(|(text, _)| !text.is_empty())
+        .unwrap_or(lines.len());
+    lines.drain(..start);
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CatalogSource, PersonaEntry};
+
+    fn source(id: &str, extends: Option<&str>, body: &str) -> PersonaSource {
+        PersonaSource {
+            entry: PersonaEntry {
+                meta: PersonaMeta {
+                    id: id.into(),
+                    name: id.to_uppercase(),
+                    description: None,
+                    tags: None,
+                    author: None,
+                    created_at: None,
+                    version: None,
+                    extends: extends.map(Into::into),
+                    extra: Default::default(),
+                },
+                uri: format!("https://example.invalid/{id}.md"),
+                root: "personas".into(),
+                handoffs: Vec::new(),
+            },
+            path: PathBuf::from(format!("personas/{id}.md")),
+            body: body.into(),
+            body_line: 5,
+            origins: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn merges_metadata_and_sections_down_the_chain() {
+        let mut base = source(
+            "base",
+            None,
+            "# BASE\n\nShared intro.\n\n## Role Snapshot\nShip.\n\n## Responsibilities Checklist\n- Test\n- Review\n",
+        );
+        base.entry.meta.description = Some("Base persona".into());
+        base.entry.meta.tags = Some(vec!["delivery".into()]);
+        base.entry
+            .meta
+            .extra
+            .insert("team".into(), serde_json::json!("core"));
+        let mut local = source(
+            "local",
+            Some("base"),
+            "# LOCAL\n\n## Responsibilities Checklist\n- Test\n- Pair\n\n## Local Notes\nAsk ops.\n",
+        );
+        local.entry.meta.tags = Some(vec!["local".into()]);
+        let nested = source("nested", Some("local"), "");
+
+        let (resolved, errors) = resolve_inheritance(vec![base, local, nested]);
+        assert!(errors.is_empty(), "{errors:?}");
+
+        let local = &resolved[1];
+        assert_eq!(
+            local.entry.meta.description.as_deref(),
+            Some("Base persona")
+        );
+        assert_eq!(local.entry.meta.tags, Some(vec!["local".to_string()]));
+        assert_eq!(
+            local.entry.meta.extra.get("team"),
+            Some(&serde_json::json!("core"))
+        );
+        assert_eq!(
+            local.body,
+            "# LOCAL\n\nShared intro.\n\n## Role Snapshot\nShip.\n\n## Responsibilities Checklist\n- Test\n- Pair\n\n## Local Notes\nAsk ops.\n"
+        );
+
+        let nested = &resolved[2];
+        assert_eq!(nested.entry.meta.extends.as_deref(), Some("local"));
+        assert!(nested.body.starts_with("# NESTED\n\nShared intro.\n"));
+        assert!(nested.body.ends_with("## Local Notes\nAsk ops.\n"));
+    }
+
+    #[test]
+    fn reports_inherited_lines_against_the_file_that_holds_them() {
+        let base = source(
+            "base",
+            None,
+            "# BASE\n\n## Role Snapshot\nShip.\n\n## When to Switch Away\n- Scope drifts → Ghost.\n",
+        );
+        let local = source(
+            "local",
+            Some("base"),
+            "# LOCAL\n\nLocal intro.\nMore.\n\n## Local Notes\nAsk ops.\n",
+        );
+        let (mut resolved, errors) = resolve_inheritance(vec![base, local]);
+        assert!(errors.is_empty(), "{errors:?}");
+        let local = &resolved[1];
+        assert_eq!(local.locate(7), (Path::new("personas/local.md"), 7));
+        assert_eq!(local.locate(14), (Path::new("personas/base.md"), 11));
+        assert_eq!(local.locate(17), (Path::new("personas/local.md"), 11));
+
+        let err = local.validate_structure().unwrap_err().to_string();
+        assert!(
+            err.contains(
+                "personas/base.md:10: missing required section `## Responsibilities Checklist`"
+            ),
+            "{err}"
+        );
+        assert!(
+            err.contains("personas/local.md:11: missing required section `## Required Artifacts`"),
+            "{err}"
+        );
+
+        let warnings = crate::resolve_handoffs(&mut resolved);
+        assert_eq!(
+            warnings.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "personas/base.md:11 hands off to `Ghost`, which names no persona",
+                "personas/base.md:11 hands off to `Ghost`, which names no persona",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_missing_parents_and_cycles_once() {
+        let (resolved, errors) = resolve_inheritance(vec![
+            source("a", Some("b"), "# A\n"),
+            source("b", Some("a"), "# B\n"),
+            source("c", Some("a"), "# C\n"),
+            source("d", Some("ghost"), "# D\n"),
+            source("e", None, "# E\n"),
+        ]);
+
+        assert_eq!(
+            resolved
+                .iter()
+                .map(|source| source.entry.meta.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["e"]
+        );
+        assert_eq!(
+            errors.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "inheritance cycle in personas/a.md: a -> b -> a",
+                "persona `d` in personas/d.md extends unknown persona `ghost`",
+            ]
+        );
+    }
+}
@@ -1,13 +1,60 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
 use std::fmt;
 use std::fs;
 use std::io;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use thiserror::Error;
 
+mod audit;
+mod cache;
+pub mod cli;
+mod compose;
+mod config;
+mod diagnostic;
+mod document;
+mod drift;
+mod handoff;
+mod inheritance;
+mod metadata;
+mod pages;
+mod schema;
+mod serve;
+mod structure;
+mod template;
+
+pub use audit::render_persona_audit;
+use cache::FileCache;
+pub use cache::SourceCache;
+pub use compose::{PromptPart, compose, compose_prompt, compose_with};
+pub use config::{CONFIG_FILE, ConfigError, DEFAULT_PAGES_BASE_URL, RepoConfig, SortOrder};
+pub use diagnostic::{Diagnostic, Severity, SourceSpan};
+pub use document::{Document, ListItem, Section};
+pub use drift::{CatalogDrift, Drift, JsonChange, catalog_drift, diff_json};
+pub use handoff::{
+    HANDOFF_SECTION, Handoff, HandoffBullet, parse_handoff_bullets, render_handoff_document,
+    render_handoff_dot, render_handoff_mermaid, resolve_handoffs,
+};
+pub use metadata::{Date, MetadataError, Version};
+pub use pages::{
+    ArtifactIssue, FileClass, Header, HeaderRule, Origin, PAGES_MANIFEST, PagesArtifact,
+    PagesError, PublishedFile, Redirect, STATIC_CONFIG, StaticConfig, build_pages, content_type,
+    validate_pages,
+};
+pub use schema::{
+    META_FIELDS, PERSONA_FIELDS, RESERVED_FIELDS, SchemaIssue, SchemaIssueKind,
+    check_front_matter_schema, check_reserved_fields,
+};
+pub use serve::{Exchange, PreviewServer, SiteRoot};
+pub use structure::{
+    Heading, PERSONA_SECTIONS, SCENARIO_SECTIONS, StructureIssue, StructureIssueKind,
+    check_persona_structure, check_scenario_structure, parse_headings,
+};
+pub use template::{TemplateContext, TemplateError, published_markdown, render_template};
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct PersonaMeta {
     pub id: String,
@@ -15,8 +62,14 @@ pub struct PersonaMeta {
     pub description: Option<String>,
     pub tags: Option<Vec<String>>,
     pub author: Option<String>,
-    pub created_at: Option<String>,
-    pub version: Option<String>,
+    pub created_at: Option<Date>,
+    pub version: Option<Version>,
+    /// Id of the persona this one inherits metadata and sections from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    /// Custom front matter fields, kept verbatim for downstream tools.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -50,15 +103,22 @@ pub enum CatalogError {
         #[source]
         source: io::Error,
     },
-    #[error("invalid front matter in {path}: {source}")]
+    #[error("invalid front matter in {path}:{}:{}: {source}", span.line, span.column)]
     FrontMatter {
         path: PathBuf,
+        span: SourceSpan,
         #[source]
         source: FrontMatterError,
     },
-    #[error("failed to parse YAML in {path}: {source}")]
+    #[error(
+        "failed to parse YAML in {path}:{}:{}: {}",
+        span.line,
+        span.column,
+        diagnostic::yaml_message(source)
+    )]
     Yaml {
         path: PathBuf,
+        span: SourceSpan,
         #[source]
         source: serde_yaml_ng::Error,
     },
@@ -68,12 +128,77 @@ pub enum CatalogError {
         #[source]
         source: serde_json::Error,
     },
-    #[error("duplicate persona id `{id}` found in {duplicate} (already defined in {first})")]
+    #[error("duplicate id `{id}` found in {duplicate} (already defined in {first})")]
     Duplicate {
         id: String,
         first: PathBuf,
         duplicate: PathBuf,
     },
+    #[error("invalid structure in {path}:{}", render_issues(path, issues))]
+    Structure {
+        path: PathBuf,
+        issues: Vec<StructureIssue>,
+    },
+    #[error(
+        "front matter schema violations in {path}:{}",
+        render_schema_issues(path, issues)
+    )]
+    Schema {
+        path: PathBuf,
+        issues: Vec<SchemaIssue>,
+    },
+    #[error("no {kind} with id `{id}` in {dir}")]
+    UnknownId {
+        kind: &'static str,
+        id: String,
+        dir: PathBuf,
+    },
+    #[error("persona `{id}` in {path} extends unknown persona `{parent}`")]
+    MissingParent {
+        path: PathBuf,
+        id: String,
+        parent: String,
+    },
+    #[error("inheritance cycle in {path}: {}", cycle.join(" -> "))]
+    InheritanceCycle { path: PathBuf, cycle: Vec<String> },
+    #[error("template error in {path}:{}:{}: {source}", span.line, span.column)]
+    Template {
+        path: PathBuf,
+        span: SourceSpan,
+        #[source]
+        source: TemplateError,
+    },
+    #[error("invalid configuration in {path}: {source}")]
+    Config {
+        path: PathBuf,
+        #[source]
+        source: ConfigError,
+    },
+}
+
+fn render_issues(path: &Path, issues: &[StructureIssue]) -> String {
+    issues
+        .iter()
+        .map(|issue| {
+            let path = issue.path.as_deref().unwrap_or(path);
+            format!("\n  {}:{}: {}", path.display(), issue.line, issue.kind)
+        })
+        .collect()
+}
+
+fn render_schema_issues(path: &Path, issues: &[SchemaIssue]) -> String {
+    issues
+        .iter()
+        .map(|issue| {
+            format!(
+                "\n  {}:{}:{}: {}",
+                path.display(),
+                issue.span.line,
+                issue.span.column,
+                issue.kind
+            )
+        })
+        .collect()
 }
 
 impl CatalogError {
@@ -84,16 +209,18 @@ impl CatalogError {
         }
     }
 
-    fn front_matter(path: &Path, source: FrontMatterError) -> Self {
+    fn front_matter(path: &Path, content: &str, source: FrontMatterError) -> Self {
         Self::FrontMatter {
             path: path.to_path_buf(),
+            span: diagnostic::front_matter_span(content, &source),
             source,
         }
     }
 
-    fn yaml(path: &Path, source: serde_yaml_ng::Error) -> Self {
+    fn yaml(path: &Path, content: &str, source: serde_yaml_ng::Error) -> Self {
         Self::Yaml {
             path: path.to_path_buf(),
+            span: diagnostic::yaml_span(content, &source),
             source,
         }
     }
@@ -112,6 +239,83 @@ impl CatalogError {
             duplicate,
         }
     }
+
+    fn structure(path: &Path, issues: Vec<StructureIssue>) -> Self {
+        Self::Structure {
+            path: path.to_path_buf(),
+            issues,
+        }
+    }
+
+    fn schema(path: &Path, issues: Vec<SchemaIssue>) -> Self {
+        Self::Schema {
+            path: path.to_path_buf(),
+            issues,
+        }
+    }
+
+    fn unknown_id(kind: &'static str, id: &str, dir: &Path) -> Self {
+        Self::UnknownId {
+            kind,
+            id: id.to_string(),
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    fn missing_parent(path: &Path, id: &str, parent: &str) -> Self {
+        Self::MissingParent {
+            path: path.to_path_buf(),
+            id: id.to_string(),
+            parent: parent.to_string(),
+        }
+    }
+
+    fn inheritance_cycle(path: &Path, cycle: Vec<String>) -> Self {
+        Self::InheritanceCycle {
+            path: path.to_path_buf(),
+            cycle,
+        }
+    }
+
+    fn template(path: &Path, span: SourceSpan, source: TemplateError) -> Self {
+        Self::Template {
+            path: path.to_path_buf(),
+            span,
+            source,
+        }
+    }
+
+    fn config(path: &Path, source: impl Into<ConfigError>) -> Self {
+        Self::Config {
+            path: path.to_path_buf(),
+            source: source.into(),
+        }
+    }
+}
+
+/// Per-run settings for walking persona and scenario directories.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CatalogOptions {
+    /// Rejects front matter keys that are neither standard fields nor in `custom_fields`.
+    pub strict: bool,
+    /// Custom front matter keys that strict mode accepts.
+    pub custom_fields: Vec<String>,
+    /// Prefixes the ids of files in subdirectories with their folder path, so
+    /// `security/AUDITOR.md` with `id: auditor` is listed as `security/auditor`.
+    pub namespace_ids: bool,
+    /// Site root for entry URIs; falls back to `PAGES_BASE_URL`, then [`DEFAULT_PAGES_BASE_URL`].
+    pub pages_base_url: Option<String>,
+    /// Order of the entries written by [`write_index`] and [`write_scenario_index`].
+    pub sort: SortOrder,
+    /// Makes [`collect`] also check each body against the structure rules.
+    pub structure: bool,
+    /// Directory that `{{> path}}` includes and entry URIs resolve against, normally the
+    /// repository root; defaults to the parent of the primary root.
+    pub repo_root: Option<PathBuf>,
+    /// Catalog names for roots, recorded as each entry's `root` and used in its URI.
+    /// Other roots are named by their path in the repository, or for a root outside it
+    /// by its directory name.
+    pub root_names: BTreeMap<PathBuf, String>,
 }
 
 pub fn parse_front_matter(content: &str) -> Result<FrontMatter<'_>, FrontMatterError> {
@@ -171,6 +375,12 @@ pub struct PersonaEntry {
     #[serde(flatten)]
     pub meta: PersonaMeta,
     pub uri: String,
+    /// The catalog root the entry was read from, relative to the primary root's parent.
+    #[serde(default)]
+    pub root: String,
+    /// Personas this one hands off to, resolved from its `When to Switch Away` section.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub handoffs: Vec<Handoff>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -185,32 +395,160 @@ impl Index {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ScenarioMeta {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub author: Option<String>,
+    pub created_at: Option<Date>,
+    pub version: Option<Version>,
+    /// Custom front matter fields, kept verbatim for downstream tools.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ScenarioEntry {
+    #[serde(flatten)]
+    pub meta: ScenarioMeta,
+    pub uri: String,
+    /// The catalog root the entry was read from, relative to the primary root's parent.
+    #[serde(default)]
+    pub root: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ScenarioIndex {
+    pub base_uri: String,
+    pub scenarios: Vec<ScenarioEntry>,
+}
+
+impl ScenarioIndex {
+    pub fn scenario_uris(&self) -> impl Iterator<Item = &str> {
+        self.scenarios.iter().map(|entry| entry.uri.as_str())
+    }
+}
+
+/// Reads every persona under `personas_dir`, stopping at the first error. Use [`collect`]
+/// to gather every error and warning in a [`CatalogReport`] instead.
+pub fn collect_persona_entries(personas_dir: &Path) -> Result<Vec<PersonaEntry>, CatalogError> {
+    let options = CatalogOptions::default();
+    let sources = collect::<PersonaSource>(&[personas_dir.to_path_buf()], &options, None);
+    Ok(sources
+        .into_result()?
+        .into_iter()
+        .map(|source| source.entry)
+        .collect())
+}
+
 pub fn generate_index(personas_dir: &Path, base_path: &Path) -> Result<Index, CatalogError> {
-    let index = build_index(personas_dir, base_path)?;
-    write_index(personas_dir, &index)?;
+    let personas = collect_persona_entries(personas_dir)?;
+    write_index(
+        personas_dir,
+        base_path,
+        personas,
+        &CatalogOptions::default(),
+    )
+}
+
+/// Sorts already collected entries in the order chosen by `options` and writes them to
+/// `personas_dir/catalog.json`.
+pub fn write_index(
+    personas_dir: &Path,
+    base_path: &Path,
+    personas: Vec<PersonaEntry>,
+    options: &CatalogOptions,
+) -> Result<Index, CatalogError> {
+    let index = build_index(personas_dir, base_path, personas, options)?;
+    write_catalog(personas_dir, &index)?;
     Ok(index)
 }
 
-fn build_index(personas_dir: &Path, base_path: &Path) -> Result<Index, CatalogError> {
+/// Builds the index [`write_index`] would write, without touching the disk.
+pub fn build_index(
+    personas_dir: &Path,
+    base_path: &Path,
+    mut personas: Vec<PersonaEntry>,
+    options: &CatalogOptions,
+) -> Result<Index, CatalogError> {
     fs::metadata(base_path).map_err(|source| CatalogError::io(base_path, source))?;
     let base_uri = resolve_base_uri(personas_dir, base_path);
-    let mut personas = collect_persona_entries(personas_dir)?;
-    personas.sort_by(|a, b| a.meta.id.cmp(&b.meta.id));
-
+    sort_entries(
+        &mut personas,
+        options.sort,
+        |entry| &entry.meta.id,
+        |entry| &entry.meta.name,
+    );
     Ok(Index { base_uri, personas })
 }
 
-fn write_index(personas_dir: &Path, index: &Index) -> Result<(), CatalogError> {
-    let catalog_path = personas_dir.join("catalog.json");
-    let mut json = serde_json::to_string_pretty(index)
-        .map_err(|source| CatalogError::json(&catalog_path, source))?;
-    json.push('\n');
+/// Sorts already collected entries in the order chosen by `options` and writes them to
+/// `scenarios_dir/catalog.json`.
+pub fn write_scenario_index(
+    scenarios_dir: &Path,
+    base_path: &Path,
+    scenarios: Vec<ScenarioEntry>,
+    options: &CatalogOptions,
+) -> Result<ScenarioIndex, CatalogError> {
+    let index = build_scenario_index(scenarios_dir, base_path, scenarios, options)?;
+    write_catalog(scenarios_dir, &index)?;
+    Ok(index)
+}
+
+/// Builds the index [`write_scenario_index`] would write, without touching the disk.
+pub fn build_scenario_index(
+    scenarios_dir: &Path,
+    base_path: &Path,
+    mut scenarios: Vec<ScenarioEntry>,
+    options: &CatalogOptions,
+) -> Result<ScenarioIndex, CatalogError> {
+    fs::metadata(base_path).map_err(|source| CatalogError::io(base_path, source))?;
+    let base_uri = resolve_base_uri(scenarios_dir, base_path);
+    sort_entries(
+        &mut scenarios,
+        options.sort,
+        |entry| &entry.meta.id,
+        |entry| &entry.meta.name,
+    );
+    Ok(ScenarioIndex {
+        base_uri,
+        scenarios,
+    })
+}
+
+/// Sorts by `order`, breaking ties between equal names by id.
+fn sort_entries<T>(
+    entries: &mut [T],
+    order: SortOrder,
+    id: impl Fn(&T) -> &String,
+    name: impl Fn(&T) -> &String,
+) {
+    match order {
+        SortOrder::Id => entries.sort_by(|a, b| id(a).cmp(id(b))),
+        SortOrder::Name => {
+            entries.sort_by(|a, b| name(a).cmp(name(b)).then_with(|| id(a).cmp(id(b))))
+        }
+    }
+}
+
+fn write_catalog<T: Serialize>(dir: &Path, index: &T) -> Result<(), CatalogError> {
+    let catalog_path = dir.join("catalog.json");
+    let json = render_catalog(index).map_err(|source| CatalogError::json(&catalog_path, source))?;
     fs::write(&catalog_path, json).map_err(|source| CatalogError::io(&catalog_path, source))?;
     Ok(())
 }
 
-fn resolve_base_uri(personas_dir: &Path, base_path: &Path) -> String {
-    personas_dir
+/// The `catalog.json` contents for `index`: pretty-printed JSON ending in a newline.
+pub fn render_catalog<T: Serialize>(index: &T) -> serde_json::Result<String> {
+    let mut json = serde_json::to_string_pretty(index)?;
+    json.push('\n');
+    Ok(json)
+}
+
+fn resolve_base_uri(catalog_dir: &Path, base_path: &Path) -> String {
+    catalog_dir
         .parent()
         .and_then(|parent| base_path.strip_prefix(parent).ok())
         .unwrap_or(base_path)
@@ -218,52 +556,591 @@ fn resolve_base_uri(personas_dir: &Path, base_path: &Path) -> String {
         .replace('\\', "/")
 }
 
-pub fn collect_persona_entries(personas_dir: &Path) -> Result<Vec<PersonaEntry>, CatalogError> {
-    let base_url = resolve_pages_base_url();
-    let mut entries = Vec::new();
-    let mut seen_ids: HashMap<String, PathBuf> = HashMap::new();
-    let read_dir =
-        fs::read_dir(personas_dir).map_err(|source| CatalogError::io(personas_dir, source))?;
-    for entry in read_dir {
-        let entry = entry.map_err(|source| CatalogError::io(personas_dir, source))?;
-        let path = entry.path();
-        if path.extension().and_then(|s| s.to_str()) != Some("md") {
+/// A persona entry together with the file it was read from and its Markdown body.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PersonaSource {
+    pub entry: PersonaEntry,
+    pub path: PathBuf,
+    /// The Markdown body. For a persona that `extends` another this is the merged
+    /// body; [`PersonaSource::locate`] maps its lines back to the files they came from.
+    pub body: String,
+    /// 1-based line of `path` on which `body` starts.
+    pub body_line: usize,
+    /// The file and line each line of a merged body was written on, starting with
+    /// `body_line`; empty when the body is the file's own.
+    pub origins: Vec<(PathBuf, usize)>,
+}
+
+impl PersonaSource {
+    /// The file and line holding `line` of the body, which is not `path` for sections
+    /// inherited from a parent persona.
+    pub fn locate(&self, line: usize) -> (&Path, usize) {
+        line.checked_sub(self.body_line)
+            .and_then(|index| self.origins.get(index))
+            .map_or((&self.path, line), |(path, line)| (path, *line))
+    }
+
+    /// The Markdown published for the persona: its front matter and body. A persona that
+    /// `extends` another gets its resolved metadata, without `extends`, so the front matter
+    /// agrees with the catalog entry.
+    pub fn published_markdown(&self) -> Result<String, CatalogError> {
+        if self.entry.meta.extends.is_none() {
+            return published_markdown(&self.path, &self.body);
+        }
+        let meta = PersonaMeta {
+            extends: None,
+            ..self.entry.meta.clone()
+        };
+        // Strings, dates, versions, and JSON values always serialize.
+        let mut yaml = serde_yaml_ng::to_value(meta).expect("persona metadata serializes");
+        if let serde_yaml_ng::Value::Mapping(fields) = &mut yaml {
+            fields.retain(|_, value| !value.is_null());
+        }
+        let yaml = serde_yaml_ng::to_string(&yaml).expect("persona metadata serializes");
+        Ok(format!("---\n{yaml}---\n{}", self.body))
+    }
+
+    /// Parses the body into sections and list items, keeping file line numbers.
+    pub fn document(&self) -> Document {
+        Document::parse(&self.body, self.body_line)
+    }
+}
+
+/// A scenario entry together with the file it was read from and its Markdown body.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ScenarioSource {
+    pub entry: ScenarioEntry,
+    pub path: PathBuf,
+    pub body: String,
+    /// 1-based line of `path` on which `body` starts.
+    pub body_line: usize,
+}
+
+impl ScenarioSource {
+    /// Parses the body into sections and list items, keeping file line numbers.
+    pub fn document(&self) -> Document {
+        Document::parse(&self.body, self.body_line)
+    }
+
+    /// The Markdown published for the scenario: its front matter as written and its body.
+    pub fn published_markdown(&self) -> Result<String, CatalogError> {
+        published_markdown(&self.path, &self.body)
+    }
+}
+
+/// A non-fatal finding reported while walking a catalog directory.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum CatalogWarning {
+    #[error("{path} has no `{field}` in its front matter; catalog listings will omit it")]
+    MissingField { path: PathBuf, field: &'static str },
+    #[error(
+        "{}:{} hands off to `{reference}`, which names no persona",
+        path.display(),
+        span.line
+    )]
+    DanglingHandoff {
+        path: PathBuf,
+        span: SourceSpan,
+        reference: String,
+    },
+    #[error("no persona hands off to `{id}` ({path})")]
+    UnreachablePersona { path: PathBuf, id: String },
+    #[error("{path} overrides `{id}` from {previous}")]
+    Overridden {
+        path: PathBuf,
+        id: String,
+        previous: PathBuf,
+    },
+}
+
+/// Every source, error, and warning gathered from a directory without stopping at the first failure.
+#[derive(Debug)]
+pub struct CatalogReport<S> {
+    pub sources: Vec<S>,
+    pub errors: Vec<CatalogError>,
+    pub warnings: Vec<CatalogWarning>,
+}
+
+impl<S> Default for CatalogReport<S> {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
+impl<S> CatalogReport<S> {
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// Returns the sources, or the first error when any were recorded.
+    pub fn into_result(self) -> Result<Vec<S>, CatalogError> {
+        match self.errors.into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(self.sources),
+        }
+    }
+
+    fn map_sources<T>(self, f: impl FnMut(S) -> T) -> CatalogReport<T> {
+        CatalogReport {
+            sources: self.sources.into_iter().map(f).collect(),
+            errors: self.errors,
+            warnings: self.warnings,
+        }
+    }
+}
+
+/// A persona or scenario file as [`collect`] returns it.
+pub trait CatalogSource: Sized {
+    /// Reads every file under `roots`, reusing the unchanged files parsed into `cache`.
+    #[doc(hidden)]
+    fn read_roots(
+        roots: &[PathBuf],
+        options: &CatalogOptions,
+        cache: &mut SourceCache,
+    ) -> CatalogReport<Self>;
+
+    /// Checks the body against SPECIFICATION §3.4 (personas) or §4.3 (scenarios),
+    /// reporting every issue at once.
+    fn validate_structure(&self) -> Result<(), CatalogError>;
+}
+
+/// Walks `roots`, recording every front matter, YAML, and duplicate-id problem, plus every
+/// structure issue when [`CatalogOptions::structure`] is set. Use `collect::<PersonaSource>`
+/// for personas and `collect::<ScenarioSource>` for scenarios.
+///
+/// `roots` are listed in increasing precedence, starting with the primary one. A later
+/// root may redefine an id from an earlier root; its source replaces the earlier one and
+/// the override is reported as [`CatalogWarning::Overridden`]. With a `cache`, files parsed
+/// by earlier calls are reused while unchanged; inheritance and handoffs are always
+/// resolved anew.
+pub fn collect<S: CatalogSource>(
+    roots: &[PathBuf],
+    options: &CatalogOptions,
+    cache: Option<&mut SourceCache>,
+) -> CatalogReport<S> {
+    let mut fresh = SourceCache::default();
+    let mut report = S::read_roots(roots, options, cache.unwrap_or(&mut fresh));
+    if options.structure {
+        report.errors.extend(
+            report
+                .sources
+                .iter()
+                .filter_map(|source| source.validate_structure().err()),
+        );
+    }
+    report
+}
+
+impl CatalogSource for PersonaSource {
+    fn read_roots(
+        roots: &[PathBuf],
+        options: &CatalogOptions,
+        cache: &mut SourceCache,
+    ) -> CatalogReport<Self> {
+        let mut report =
+            collect_markdown_report(roots, options, &mut cache.personas).map_sources(|source| {
+                PersonaSource {
+                    entry: PersonaEntry {
+                        meta: source.meta,
+                        uri: source.uri,
+                        root: source.root,
+                        handoffs: Vec::new(),
+                    },
+                    path: source.path,
+                    body: source.body,
+                    body_line: source.body_line,
+                    origins: Vec::new(),
+                }
+            });
+        let (sources, errors) = inheritance::resolve_inheritance(report.sources);
+        report.sources = sources;
+        report.errors.extend(errors);
+        for source in &report.sources {
+            if source.entry.meta.extends.is_some() {
+                report
+                    .warnings
+                    .extend(missing_field_warnings(&source.entry.meta, &source.path));
+            }
+        }
+        let warnings = resolve_handoffs(&mut report.sources);
+        report.warnings.extend(warnings);
+        report
+    }
+
+    fn validate_structure(&self) -> Result<(), CatalogError> {
+        let mut issues = check_persona_structure(&self.entry.meta.name, &self.body, self.body_line);
+        for issue in &mut issues {
+            let (path, line) = self.locate(issue.line);
+            issue.path = (path != self.path).then(|| path.to_path_buf());
+            issue.line = line;
+        }
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(CatalogError::structure(&self.path, issues))
+        }
+    }
+}
+
+impl CatalogSource for ScenarioSource {
+    fn read_roots(
+        roots: &[PathBuf],
+        options: &CatalogOptions,
+        cache: &mut SourceCache,
+    ) -> CatalogReport<Self> {
+        collect_markdown_report(roots, options, &mut cache.scenarios).map_sources(|source| {
+            ScenarioSource {
+                entry: ScenarioEntry {
+                    meta: source.meta,
+                    uri: source.uri,
+                    root: source.root,
+                },
+                path: source.path,
+                body: source.body,
+                body_line: source.body_line,
+            }
+        })
+    }
+
+    fn validate_structure(&self) -> Result<(), CatalogError> {
+        let issues = check_scenario_structure(&self.entry.meta.name, &self.body, self.body_line);
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(CatalogError::structure(&self.path, issues))
+        }
+    }
+}
+
+/// Front matter models that can be indexed by [`collect_markdown_report`].
+trait CatalogMeta: DeserializeOwned {
+    /// Front matter keys strict mode accepts.
+    const FIELDS: &'static [&'static str];
+
+    fn id(&self) -> &str;
+    fn id_mut(&mut self) -> &mut String;
+    fn description(&self) -> Option<&str>;
+    fn tags(&self) -> Option<&[String]>;
+
+    /// The parent id; missing fields are only reported once inheritance is resolved.
+    fn extends(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl CatalogMeta for PersonaMeta {
+    const FIELDS: &'static [&'static str] = PERSONA_FIELDS;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn id_mut(&mut self) -> &mut String {
+        &mut self.id
+    }
+
+    fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    fn tags(&self) -> Option<&[String]> {
+        self.tags.as_deref()
+    }
+
+    fn extends(&self) -> Option<&str> {
+        self.extends.as_deref()
+    }
+}
+
+impl CatalogMeta for ScenarioMeta {
+    const FIELDS: &'static [&'static str] = META_FIELDS;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn id_mut(&mut self) -> &mut String {
+        &mut self.id
+    }
+
+    fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    fn tags(&self) -> Option<&[String]> {
+        self.tags.as_deref()
+    }
+}
+
+#[derive(Clone)]
+struct MarkdownSource<M> {
+    meta: M,
+    uri: String,
+    root: String,
+    path: PathBuf,
+    body: String,
+    body_line: usize,
+    /// Snippets the body includes, directly or through other snippets.
+    includes: Vec<PathBuf>,
+}
+
+fn collect_markdown_report<M: CatalogMeta + Clone>(
+    roots: &[PathBuf],
+    options: &CatalogOptions,
+    cache: &mut FileCache<M>,
+) -> CatalogReport<MarkdownSource<M>> {
+    let mut report = CatalogReport::default();
+    let Some(primary) = roots.first() else {
+        return report;
+    };
+    cache.start();
+
+    let repo_root = options
+        .repo_root
+        .clone()
+        .unwrap_or_else(|| primary.parent().unwrap_or(primary).to_path_buf());
+    let base_url = options
+        .pages_base_url
+        .clone()
+        .unwrap_or_else(resolve_pages_base_url);
+    // Maps each id to the root it was last defined in and its position in `sources`.
+    let mut seen_ids: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut names: HashMap<String, &PathBuf> = HashMap::new();
+    for (root_index, dir) in roots.iter().enumerate() {
+        let paths = match markdown_paths(dir) {
+            Ok(paths) => paths,
+            Err(err) => {
+                report.errors.push(err);
+                continue;
+            }
+        };
+        let root = root_label(dir, &repo_root, &options.root_names);
+        if let Some(other) = names.get(&root).filter(|other| **other != dir) {
+            let duplicate = ConfigError::DuplicateRootName {
+                root: dir.clone(),
+                name: root,
+                other: other.to_path_buf(),
+            };
+            report.errors.push(CatalogError::config(dir, duplicate));
             continue;
         }
-        let content =
-            fs::read_to_string(&path).map_err(|source| CatalogError::io(&path, source))?;
-        let front_matter = parse_front_matter(&content)
-            .map_err(|source| CatalogError::front_matter(&path, source))?;
-        let meta: PersonaMeta = serde_yaml_ng::from_str(front_matter.yaml.as_ref())
-            .map_err(|source| CatalogError::yaml(&path, source))?;
-        let id = meta.id.clone();
-        if let Some(first) = seen_ids.insert(id.clone(), path.clone()) {
-            return Err(CatalogError::duplicate(id, first, path));
+        names.insert(root.clone(), dir);
+        for path in paths {
+            let read =
+                || read_markdown_source::<M>(&path, dir, &root, &repo_root, &base_url, options);
+            let source = match cache.read(&path, read) {
+                Ok(source) => source,
+                Err(err) => {
+                    report.errors.push(err);
+                    continue;
+                }
+            };
+            let id = source.meta.id().to_string();
+            let seen = seen_ids.get(&id).copied();
+            if let Some((seen_root, index)) = seen
+                && seen_root == root_index
+            {
+                let first = report.sources[index].path.clone();
+                report.errors.push(CatalogError::duplicate(id, first, path));
+                continue;
+            }
+
+            if source.meta.extends().is_none() {
+                report
+                    .warnings
+                    .extend(missing_field_warnings(&source.meta, &path));
+            }
+            match seen {
+                Some((_, index)) => {
+                    report.warnings.push(CatalogWarning::Overridden {
+                        path,
+                        id: id.clone(),
+                        previous: report.sources[index].path.clone(),
+                    });
+                    report.sources[index] = source;
+                    seen_ids.insert(id, (root_index, index));
+                }
+                None => {
+                    seen_ids.insert(id, (root_index, report.sources.len()));
+                    report.sources.push(source);
+                }
+            }
         }
-        let uri = build_persona_uri(&path, personas_dir, &base_url);
-        entries.push(PersonaEntry { meta, uri });
     }
-    Ok(entries)
+    cache.finish();
+    report
 }
 
-fn resolve_pages_base_url() -> String {
-    env::var("PAGES_BASE_URL").unwrap_or_else(|_| "https://qqrm.github.io/codex-tools".to_string())
+/// Names `dir` in the catalog: its name in `names`, else its path relative to the
+/// repository root, e.g. `personas`, else its directory name.
+fn root_label(dir: &Path, repo_root: &Path, names: &BTreeMap<PathBuf, String>) -> String {
+    let dir = lexical(dir);
+    if let Some((_, name)) = names.iter().find(|(root, _)| lexical(root) == dir) {
+        return name.clone();
+    }
+    match dir.strip_prefix(lexical(repo_root)) {
+        Ok(relative) => slash_path(relative),
+        Err(_) => dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    }
 }
 
-fn build_persona_uri(path: &Path, personas_dir: &Path, base_url: &str) -> String {
-    let normalized_base = base_url.trim_end_matches('/');
-    let relative_path = personas_dir
-        .parent()
-        .and_then(|root| path.strip_prefix(root).ok())
-        .unwrap_or(path);
+/// `path` with `.` and `..` resolved without touching the file system.
+fn lexical(path: &Path) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            _ => resolved.push(component),
+        }
+    }
+    resolved
+}
 
-    let relative = relative_path
-        .components()
+/// Joins the components of a relative path with `/`.
+fn slash_path(path: &Path) -> String {
+    path.components()
         .map(|component| component.as_os_str().to_string_lossy())
         .collect::<Vec<_>>()
-        .join("/");
+        .join("/")
+}
+
+fn missing_field_warnings<M: CatalogMeta>(meta: &M, path: &Path) -> Vec<CatalogWarning> {
+    let mut warnings = Vec::new();
+    if meta.description().is_none() {
+        warnings.push(CatalogWarning::MissingField {
+            path: path.to_path_buf(),
+            field: "description",
+        });
+    }
+    if meta.tags().is_none_or(<[String]>::is_empty) {
+        warnings.push(CatalogWarning::MissingField {
+            path: path.to_path_buf(),
+            field: "tags",
+        });
+    }
+    warnings
+}
+
+/// Lists the Markdown files under `dir` and its subdirectories, sorted so diagnostics
+/// are reproducible.
+///
+/// Hidden directories are skipped, and symlinked directories are followed but each
+/// directory is walked at most once, so symlink loops terminate.
+fn markdown_paths(dir: &Path) -> Result<Vec<PathBuf>, CatalogError> {
+    let mut paths = Vec::new();
+    walk_markdown(dir, &mut HashSet::new(), &mut paths)?;
+    paths.sort();
+    Ok(paths)
+}
+
+fn walk_markdown(
+    dir: &Path,
+    visited: &mut HashSet<PathBuf>,
+    paths: &mut Vec<PathBuf>,
+) -> Result<(), CatalogError> {
+    let canonical = fs::canonicalize(dir).map_err(|source| CatalogError::io(dir, source))?;
+    if !visited.insert(canonical) {
+        return Ok(());
+    }
+    let read_dir = fs::read_dir(dir).map_err(|source| CatalogError::io(dir, source))?;
+    for entry in read_dir {
+        let entry = entry.map_err(|source| CatalogError::io(dir, source))?;
+        let path = entry.path();
+        if path.is_dir() {
+            if !entry.file_name().to_string_lossy().starts_with('.') {
+                walk_markdown(&path, visited, paths)?;
+            }
+        } else if path.extension().and_then(|s| s.to_str()) == Some("md") {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
 
-    format!("{}/{}", normalized_base, relative)
+/// The folders between `dir` and `path`, joined with `/`, e.g. `security` for
+/// `personas/security/AUDITOR.md`.
+fn namespace(path: &Path, dir: &Path) -> String {
+    path.parent()
+        .and_then(|parent| parent.strip_prefix(dir).ok())
+        .map(slash_path)
+        .unwrap_or_default()
+}
+
+fn read_markdown_source<M: CatalogMeta>(
+    path: &Path,
+    dir: &Path,
+    root: &str,
+    repo_root: &Path,
+    base_url: &str,
+    options: &CatalogOptions,
+) -> Result<MarkdownSource<M>, CatalogError> {
+    let content = fs::read_to_string(path).map_err(|source| CatalogError::io(path, source))?;
+    let front_matter = parse_front_matter(&content)
+        .map_err(|source| CatalogError::front_matter(path, &content, source))?;
+    let mut meta: M = serde_yaml_ng::from_str(front_matter.yaml.as_ref())
+        .map_err(|source| CatalogError::yaml(path, &content, source))?;
+    let issues = if options.strict {
+        check_front_matter_schema(&content, M::FIELDS, &options.custom_fields)
+    } else {
+        check_reserved_fields(&content)
+    };
+    if !issues.is_empty() {
+        return Err(CatalogError::schema(path, issues));
+    }
+    let namespace = namespace(path, dir);
+    if options.namespace_ids && !namespace.is_empty() {
+        let id = meta.id_mut();
+        *id = format!("{namespace}/{id}");
+    }
+    let uri = build_persona_uri(path, dir, root, base_url);
+    let body_line = body_start_line(&content, &front_matter.body);
+    let context = TemplateContext::for_source(repo_root, base_url, meta.id());
+    let (body, includes) =
+        template::expand_template(path, &front_matter.body, body_line, &context)?;
+    Ok(MarkdownSource {
+        meta,
+        uri,
+        root: root.to_string(),
+        path: path.to_path_buf(),
+        body,
+        body_line,
+        includes,
+    })
+}
+
+/// Returns the 1-based line of `content` on which the front matter `body` begins.
+///
+/// The body is always a suffix of the (CRLF-normalized) content, so counting the
+/// newlines that precede it yields the number of front matter lines.
+fn body_start_line(content: &str, body: &str) -> usize {
+    let normalized = content.replace("\r\n", "\n");
+    let prefix_len = normalized.len().saturating_sub(body.len());
+    normalized[..prefix_len].matches('\n').count() + 1
+}
+
+fn resolve_pages_base_url() -> String {
+    env::var("PAGES_BASE_URL").unwrap_or_else(|_| DEFAULT_PAGES_BASE_URL.to_string())
+}
+
+/// The published address of `path`, found under `dir`, whose repository-relative name is `root`.
+fn build_persona_uri(path: &Path, dir: &Path, root: &str, base_url: &str) -> String {
+    let normalized_base = base_url.trim_end_matches('/');
+    let relative = slash_path(path.strip_prefix(dir).unwrap_or(path));
+    if root.is_empty() {
+        format!("{normalized_base}/{relative}")
+    } else {
+        format!("{normalized_base}/{root}/{relative}")
+    }
 }
 
 #[cfg(test)]
@@ -273,6 +1150,35 @@ mod tests {
     use std::error::Error;
     use tempfile::tempdir;
 
+    fn personas_in(dir: &Path, options: &CatalogOptions) -> CatalogReport<PersonaSource> {
+        collect(&[dir.to_path_buf()], options, None)
+    }
+
+    #[test]
+    fn collect_persona_entries_stops_at_the_first_error() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        fs::write(
+            tmp.path().join("ONE.md"),
+            "---\nid: one\nname: One\n---\n# One\n",
+        )?;
+        let entries = collect_persona_entries(tmp.path())?;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].meta.id, "one");
+        assert!(entries[0].uri.ends_with("/ONE.md"), "{}", entries[0].uri);
+
+        fs::write(tmp.path().join("TWO.md"), "no front matter\n")?;
+        fs::write(tmp.path().join("THREE.md"), "no front matter\n")?;
+        let err = collect_persona_entries(tmp.path()).unwrap_err();
+        assert!(matches!(err, CatalogError::FrontMatter { .. }), "{err:?}");
+        assert_eq!(
+            personas_in(tmp.path(), &CatalogOptions::default())
+                .errors
+                .len(),
+            2
+        );
+        Ok(())
+    }
+
     #[test]
     fn parses_with_extra_delimiter() {
         let content = "---\nid: 1\n---\n---\nbody\n";
@@ -396,4 +1302,568 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn generates_scenario_index_with_expected_fields() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let root = tmp.path();
+        let scenarios = root.join("scenarios");
+        fs::create_dir(&scenarios)?;
+
+        fs::write(
+            scenarios.join("B.md"),
+            "---\nid: b_scenario\nname: B\ntags: [one, two]\n---\nbody\n",
+        )?;
+        fs::write(
+            scenarios.join("A.md"),
+            "---\nid: a_scenario\nname: A\n---\nbody\n",
+        )?;
+        fs::write(root.join("AGENTS.md"), "Base instructions\n")?;
+
+        let options = CatalogOptions::default();
+        let entries = collect::<ScenarioSource>(std::slice::from_ref(&scenarios), &options, None)
+            .into_result()?
+            .into_iter()
+            .map(|source| source.entry)
+            .collect();
+        let index = write_scenario_index(&scenarios, &root.join("AGENTS.md"), entries, &options)?;
+        assert_eq!(index.base_uri, "AGENTS.md");
+        assert_eq!(
+            index
+                .scenarios
+                .iter()
+                .map(|entry| entry.meta.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a_scenario", "b_scenario"]
+        );
+        assert!(index.scenarios[0].uri.ends_with("/scenarios/A.md"));
+
+        let json = fs::read_to_string(scenarios.join("catalog.json"))?;
+        assert!(json.contains("\"tags\": [\n        \"one\",\n        \"two\"\n      ]"));
+        let parsed: ScenarioIndex = serde_json::from_str(&json)?;
+        assert_eq!(parsed, index);
+
+        Ok(())
+    }
+
+    #[test]
+    fn carries_custom_fields_into_catalog() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let root = tmp.path();
+        let personas = root.join("personas");
+        fs::create_dir(&personas)?;
+        fs::write(
+            personas.join("ONE.md"),
+            "---\nid: one\nname: One\nversion: 0.10\naudience: [backend, sre]\nmodel_hints:\n  temperature: 0.2\n---\nbody\n",
+        )?;
+        fs::write(root.join("AGENTS.md"), "Base instructions\n")?;
+
+        let index = generate_index(&personas, &root.join("AGENTS.md"))?;
+        let meta = &index.personas[0].meta;
+        assert_eq!(
+            meta.version.map(|version| version.to_string()).as_deref(),
+            Some("0.10")
+        );
+        assert_eq!(
+            meta.extra.keys().collect::<Vec<_>>(),
+            vec!["audience", "model_hints"]
+        );
+        assert_eq!(
+            meta.extra["audience"],
+            serde_json::json!(["backend", "sre"])
+        );
+
+        let json = fs::read_to_string(personas.join("catalog.json"))?;
+        assert!(json.contains("\"model_hints\": {\n        \"temperature\": 0.2\n      }"));
+        let parsed: Index = serde_json::from_str(&json)?;
+        assert_eq!(parsed, index);
+
+        fs::write(
+            personas.join("TWO.md"),
+            "---\nid: two\nname: Two\nuri: https://example.invalid\n---\n",
+        )?;
+        let report = personas_in(&personas, &CatalogOptions::default());
+        assert!(matches!(
+            report.errors.as_slice(),
+            [CatalogError::Schema { issues, .. }] if issues[0].span.line == 4
+        ));
+
+        // `root` is written by the generator too, so it must not appear twice in an entry.
+        fs::write(
+            personas.join("TWO.md"),
+            "---\nid: two\nname: Two\nroot: custom\n---\n",
+        )?;
+        for strict in [false, true] {
+            let options = CatalogOptions {
+                strict,
+                custom_fields: vec!["audience".into(), "model_hints".into()],
+                ..CatalogOptions::default()
+            };
+            let report = personas_in(&personas, &options);
+            assert!(matches!(
+                report.errors.as_slice(),
+                [CatalogError::Schema { issues, .. }] if issues.len() == 1 && issues[0].span.line == 4
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn emits_handoffs_into_catalog() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let root = tmp.path();
+        let personas = root.join("personas");
+        fs::create_dir(&personas)?;
+        fs::write(
+            personas.join("A.md"),
+            "---\nid: a\nname: Alpha\n---\n# Alpha\n\n## When to Switch Away\n- Ready to ship → hand off to Beta.\n",
+        )?;
+        fs::write(
+            personas.join("B.md"),
+            "---\nid: b\nname: Beta\n---\n# Beta\n\n## When to Switch Away\n- Scope changes → back to `a`.\n",
+        )?;
+        fs::write(root.join("AGENTS.md"), "Base instructions\n")?;
+
+        let index = generate_index(&personas, &root.join("AGENTS.md"))?;
+        assert_eq!(
+            index.personas[0].handoffs,
+            vec![Handoff {
+                to: "b".into(),
+                trigger: "Ready to ship".into(),
+            }]
+        );
+        assert_eq!(index.personas[1].handoffs[0].to, "a");
+
+        let json = fs::read_to_string(personas.join("catalog.json"))?;
+        assert!(json.contains("\"handoffs\": [\n        {\n          \"to\": \"b\","));
+        let parsed: Index = serde_json::from_str(&json)?;
+        assert_eq!(parsed, index);
+        Ok(())
+    }
+
+    #[test]
+    fn resolves_extends_into_catalog() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let personas = tmp.path().join("personas");
+        fs::create_dir(&personas)?;
+        fs::write(
+            personas.join("BASE.md"),
+            "---\nid: base\nname: Base\ndescription: Ships changes\ntags: [delivery]\n---\n# Base\n\n## Responsibilities Checklist\n- Test\n",
+        )?;
+        fs::write(
+            personas.join("LOCAL.md"),
+            "---\nid: local\nname: Local\nextends: base\n---\n# Local\n\n## Responsibilities Checklist\n- Pair\n",
+        )?;
+
+        let report = personas_in(
+            &personas,
+            &CatalogOptions {
+                strict: true,
+                ..CatalogOptions::default()
+            },
+        );
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+        let local = &report.sources[1];
+        assert_eq!(local.entry.meta.tags, Some(vec!["delivery".to_string()]));
+        assert_eq!(
+            local
+                .document()
+                .section("Responsibilities Checklist")
+                .map(Section::bullets),
+            Some(vec!["Pair".to_string()])
+        );
+
+        let entries = personas_in(&personas, &CatalogOptions::default())
+            .into_result()?
+            .into_iter()
+            .map(|source| source.entry)
+            .collect::<Vec<_>>();
+        let json = serde_json::to_string(&entries)?;
+        assert!(json.contains("\"extends\":\"base\""));
+        assert!(!json.contains("\"extends\":null"));
+
+        fs::write(
+            personas.join("BASE.md"),
+            "---\nid: base\nname: Base\nextends: missing\n---\n# Base\n",
+        )?;
+        let err = personas_in(&personas, &CatalogOptions::default())
+            .into_result()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CatalogError::MissingParent { ref id, ref parent, .. } if id == "base" && parent == "missing"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn later_roots_override_ids_but_not_within_a_root() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let public = tmp.path().join("personas");
+        let private = tmp.path().join("private");
+        fs::create_dir(&public)?;
+        fs::create_dir(&private)?;
+        fs::write(public.join("A.md"), "---\nid: a\nname: Public\n---\n")?;
+        fs::write(private.join("A.md"), "---\nid: a\nname: Private\n---\n")?;
+        fs::write(private.join("B.md"), "---\nid: a\nname: Again\n---\n")?;
+
+        let roots = [public.clone(), private.clone()];
+        let report = collect::<PersonaSource>(&roots, &CatalogOptions::default(), None);
+        assert_eq!(report.sources.len(), 1);
+        assert_eq!(report.sources[0].entry.meta.name, "Private");
+        assert_eq!(report.sources[0].entry.root, "private");
+        assert!(report.warnings.contains(&CatalogWarning::Overridden {
+            path: private.join("A.md"),
+            id: "a".into(),
+            previous: public.join("A.md"),
+        }));
+        assert!(matches!(
+            report.errors.as_slice(),
+            [CatalogError::Duplicate { first, duplicate, .. }]
+                if *first == private.join("A.md") && *duplicate == private.join("B.md")
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn names_roots_inside_and_outside_the_repository() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let repo = tmp.path().join("repo");
+        let external = tmp.path().join("ext");
+        fs::create_dir_all(repo.join("personas"))?;
+        fs::create_dir(&external)?;
+        fs::write(
+            external.join("EXTRA.md"),
+            "---\nid: extra\nname: Extra\n---\n",
+        )?;
+        let mut options = CatalogOptions {
+            repo_root: Some(repo.clone()),
+            pages_base_url: Some("https://example.invalid".into()),
+            ..CatalogOptions::default()
+        };
+
+        for root in [external.clone(), repo.join("..").join("ext")] {
+            let report =
+                collect::<PersonaSource>(&[repo.join("personas"), root.clone()], &options, None);
+            assert!(report.errors.is_empty(), "{:?}", report.errors);
+            assert_eq!(report.sources[0].entry.root, "ext");
+            assert_eq!(
+                report.sources[0].entry.uri,
+                "https://example.invalid/ext/EXTRA.md"
+            );
+        }
+
+        let nested = repo.join("overlays").join("private");
+        fs::create_dir_all(&nested)?;
+        fs::write(
+            nested.join("EXTRA.md"),
+            "---\nid: extra\nname: Extra\n---\n",
+        )?;
+        let report =
+            collect::<PersonaSource>(&[repo.join("personas"), nested.clone()], &options, None);
+        assert_eq!(report.sources[0].entry.root, "overlays/private");
+        assert_eq!(
+            report.sources[0].entry.uri,
+            "https://example.invalid/overlays/private/EXTRA.md"
+        );
+
+        options
+            .root_names
+            .insert(repo.join("..").join("ext"), "private".into());
+        let report =
+            collect::<PersonaSource>(&[repo.join("personas"), external.clone()], &options, None);
+        assert_eq!(report.sources[0].entry.root, "private");
+
+        options
+            .root_names
+            .insert(external.clone(), "overlays/private".into());
+        let report = collect::<PersonaSource>(
+            &[repo.join("personas"), nested.clone(), external.clone()],
+            &options,
+            None,
+        );
+        assert!(matches!(
+            report.errors.as_slice(),
+            [CatalogError::Config { path, source: ConfigError::DuplicateRootName { name, other, .. } }]
+                if *path == external && name == "overlays/private" && *other == nested
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn resolves_includes_against_the_repository_root() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let overlay = tmp.path().join("overlays").join("private");
+        fs::create_dir_all(overlay.join("team"))?;
+        fs::create_dir(tmp.path().join("snippets"))?;
+        fs::write(tmp.path().join("snippets").join("loop.md"), "- Test.\n")?;
+        fs::write(
+            overlay.join("team").join("A.md"),
+            "---\nid: a\nname: A\n---\n{{> snippets/loop.md}}\n",
+        )?;
+
+        let options = CatalogOptions {
+            repo_root: Some(tmp.path().to_path_buf()),
+            ..CatalogOptions::default()
+        };
+        let report = collect::<PersonaSource>(&[overlay], &options, None);
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        assert_eq!(report.sources[0].body, "- Test.\n");
+        Ok(())
+    }
+
+    #[test]
+    fn discovers_nested_personas_and_namespaces_ids() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let personas = tmp.path().join("personas");
+        let security = personas.join("security");
+        fs::create_dir_all(security.join("cloud"))?;
+        fs::create_dir(personas.join(".drafts"))?;
+        fs::write(personas.join("A.md"), "---\nid: a\nname: A\n---\n")?;
+        fs::write(
+            security.join("AUDITOR.md"),
+            "---\nid: auditor\nname: Auditor\n---\n",
+        )?;
+        fs::write(
+            security.join("cloud").join("IAM.md"),
+            "---\nid: iam\nname: IAM\n---\nSee {{ id }}.\n",
+        )?;
+        fs::write(
+            personas.join(".drafts").join("WIP.md"),
+            "not front matter\n",
+        )?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&personas, security.join("loop"))?;
+
+        let report = personas_in(&personas, &CatalogOptions::default());
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        let ids = report
+            .sources
+            .iter()
+            .map(|source| source.entry.meta.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["a", "auditor", "iam"]);
+        assert!(
+            report.sources[2]
+                .entry
+                .uri
+                .ends_with("/personas/security/cloud/IAM.md")
+        );
+
+        let options = CatalogOptions {
+            namespace_ids: true,
+            ..CatalogOptions::default()
+        };
+        let report = personas_in(&personas, &options);
+        let ids = report
+            .sources
+            .iter()
+            .map(|source| source.entry.meta.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["a", "security/auditor", "security/cloud/iam"]);
+        assert_eq!(report.sources[2].body, "See security/cloud/iam.\n");
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_duplicate_scenario_ids() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let scenarios = tmp.path().join("scenarios");
+        fs::create_dir(&scenarios)?;
+        fs::write(scenarios.join("A.md"), "---\nid: same\nname: A\n---\n")?;
+        fs::write(scenarios.join("B.md"), "---\nid: same\nname: B\n---\n")?;
+
+        let err = collect::<ScenarioSource>(&[scenarios], &CatalogOptions::default(), None)
+            .into_result()
+            .unwrap_err();
+        assert!(matches!(err, CatalogError::Duplicate { ref id, .. } if id == "same"));
+        Ok(())
+    }
+
+    #[test]
+    fn reports_body_start_line_and_structure_issues() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let personas = tmp.path().join("personas");
+        fs::create_dir(&personas)?;
+        fs::write(
+            personas.join("ONE.md"),
+            "---\r\nid: one\r\nname: One\r\n---\r\n# Uno\r\n\r\n## Role Snapshot\r\n",
+        )?;
+
+        let sources = personas_in(&personas, &CatalogOptions::default()).into_result()?;
+        assert_eq!(sources[0].body_line, 5);
+
+        let options = CatalogOptions {
+            structure: true,
+            ..CatalogOptions::default()
+        };
+        let err = personas_in(&personas, &options).into_result().unwrap_err();
+        let CatalogError::Structure { issues, .. } = &err else {
+            panic!("expected structure error, got {err:?}");
+        };
+        assert_eq!(issues.len(), 5);
+        assert_eq!(issues[0].line, 5);
+        assert!(
+            err.to_string()
+                .contains("ONE.md:5: level-one heading `# Uno` does not match name `One`")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn exposes_persona_sections_as_documents() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let personas = tmp.path().join("personas");
+        fs::create_dir(&personas)?;
+        fs::write(
+            personas.join("DEV.md"),
+            "---\nid: delivery_engineer\nname: Delivery Engineer\n---\n# Delivery Engineer\n\n## Required Artifacts\n- Passing tests.\n- Release notes.\n",
+        )?;
+
+        let sources = personas_in(&personas, &CatalogOptions::default()).into_result()?;
+        let document = sources[0].document();
+        let artifacts = document
+            .section("Required Artifacts")
+            .ok_or("missing section")?;
+        assert_eq!(
+            artifacts.bullets(),
+            vec!["Passing tests.", "Release notes."]
+        );
+        assert_eq!(artifacts.items[1].line, 9);
+        Ok(())
+    }
+
+    #[test]
+    fn report_collects_every_error_and_warning() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let personas = tmp.path().join("personas");
+        fs::create_dir(&personas)?;
+        fs::write(personas.join("A.md"), "no front matter\n")?;
+        fs::write(personas.join("B.md"), "---\nid: [unclosed\n---\n")?;
+        fs::write(
+            personas.join("C.md"),
+            "---\nid: c\nname: C\ndescription: C\ntags: [c]\n---\n",
+        )?;
+        fs::write(personas.join("D.md"), "---\nid: c\nname: D\n---\n")?;
+        fs::write(personas.join("E.md"), "---\nid: e\nname: E\n---\n")?;
+
+        let report = personas_in(&personas, &CatalogOptions::default());
+        assert!(report.has_errors());
+        assert!(matches!(report.errors[0], CatalogError::FrontMatter { .. }));
+        assert!(matches!(report.errors[1], CatalogError::Yaml { .. }));
+        assert!(matches!(
+            report.errors[2],
+            CatalogError::Duplicate { ref id, .. } if id == "c"
+        ));
+        assert_eq!(report.errors.len(), 3);
+        assert_eq!(
+            report
+                .sources
+                .iter()
+                .map(|source| source.entry.meta.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["c", "e"]
+        );
+        assert_eq!(
+            report.warnings,
+            vec![
+                CatalogWarning::MissingField {
+                    path: personas.join("E.md"),
+                    field: "description",
+                },
+                CatalogWarning::MissingField {
+                    path: personas.join("E.md"),
+                    field: "tags",
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn strict_mode_reports_schema_violations() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let personas = tmp.path().join("personas");
+        fs::create_dir(&personas)?;
+        fs::write(
+            personas.join("ONE.md"),
+            "---\nid: one\nname: One\ndescripton: typo\ntag: [a]\nfoo: 1\n---\n",
+        )?;
+
+        let lenient = personas_in(&personas, &CatalogOptions::default());
+        assert!(!lenient.has_errors());
+        assert_eq!(
+            lenient.sources[0].entry.meta.extra.get("foo"),
+            Some(&serde_json::json!(1))
+        );
+
+        let strict = personas_in(
+            &personas,
+            &CatalogOptions {
+                strict: true,
+                ..CatalogOptions::default()
+            },
+        );
+        let [CatalogError::Schema { issues, .. }] = strict.errors.as_slice() else {
+            panic!("expected one schema error, got {:?}", strict.errors);
+        };
+        assert_eq!(issues.len(), 3);
+        let message = strict.errors[0].to_string();
+        assert!(
+            message.contains("ONE.md:4:1: unknown field `descripton`; did you mean `description`?"),
+            "{message}"
+        );
+        assert!(
+            message.contains("ONE.md:6:1: unknown field `foo`"),
+            "{message}"
+        );
+
+        let allowed = personas_in(
+            &personas,
+            &CatalogOptions {
+                strict: true,
+                custom_fields: vec!["foo".into()],
+                ..CatalogOptions::default()
+            },
+        );
+        let [CatalogError::Schema { issues, .. }] = allowed.errors.as_slice() else {
+            panic!("expected one schema error, got {:?}", allowed.errors);
+        };
+        assert_eq!(issues.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_invalid_dates_and_versions_in_every_mode() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let personas = tmp.path().join("personas");
+        fs::create_dir(&personas)?;
+        fs::write(
+            personas.join("ONE.md"),
+            "---\nid: one\nname: One\ncreated_at: 2025-02-30\n---\n",
+        )?;
+        fs::write(
+            personas.join("TWO.md"),
+            "---\nid: two\nname: Two\nversion: 1\n---\n",
+        )?;
+
+        let report = personas_in(&personas, &CatalogOptions::default());
+        let messages = report
+            .errors
+            .iter()
+            .flat_map(CatalogError::diagnostics)
+            .map(|diagnostic| diagnostic.render_machine())
+            .collect::<Vec<_>>();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].contains(
+            "ONE.md:4:13: error: failed to parse YAML: created_at: `2025-02-30` is not a valid YYYY-MM-DD date"
+        ));
+        assert!(
+            messages[1]
+                .contains("TWO.md:4:10: error: failed to parse YAML: version: `1` does not match")
+        );
+        Ok(())
+    }
 }
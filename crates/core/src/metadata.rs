@@ -0,0 +1,201 @@
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// A `created_at` or `version` value that does not match its documented format.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MetadataError {
+    #[error("`{0}` is not a valid YYYY-MM-DD date")]
+    InvalidDate(String),
+    #[error("`{0}` does not match MAJOR.MINOR or MAJOR.MINOR.PATCH")]
+    InvalidVersion(String),
+}
+
+/// A calendar date written as `YYYY-MM-DD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl FromStr for Date {
+    type Err = MetadataError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || MetadataError::InvalidDate(value.to_string());
+        let bytes = value.as_bytes();
+        if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+            return Err(invalid());
+        }
+        let digits = |range: std::ops::Range<usize>| -> Option<u16> {
+            let part = &value[range];
+            part.bytes()
+                .all(|b| b.is_ascii_digit())
+                .then(|| part.parse().ok())
+                .flatten()
+        };
+        let (Some(year), Some(month), Some(day)) = (digits(0..4), digits(5..7), digits(8..10))
+        else {
+            return Err(invalid());
+        };
+        let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+        let days_in_month = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap => 29,
+            2 => 28,
+            _ => return Err(invalid()),
+        };
+        if !(1..=days_in_month).contains(&day) {
+            return Err(invalid());
+        }
+        Ok(Self {
+            year,
+            month: month as u8,
+            day: day as u8,
+        })
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// A `MAJOR.MINOR` or `MAJOR.MINOR.PATCH` version.
+///
+/// Components are compared numerically, so `0.10` sorts after `0.9`, and the
+/// original spelling is kept so `0.2` never turns into `0.2.0` in the catalog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: Option<u64>,
+}
+
+impl FromStr for Version {
+    type Err = MetadataError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || MetadataError::InvalidVersion(value.to_string());
+        let parts = value
+            .split('.')
+            .map(|part| {
+                let numeric = !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+                let canonical = part.len() == 1 || !part.starts_with('0');
+                (numeric && canonical)
+                    .then(|| part.parse::<u64>().ok())
+                    .flatten()
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(invalid)?;
+        match parts.as_slice() {
+            [major, minor] => Ok(Self {
+                major: *major,
+                minor: *minor,
+                patch: None,
+            }),
+            [major, minor, patch] => Ok(Self {
+                major: *major,
+                minor: *minor,
+                patch: Some(*patch),
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)?;
+        if let Some(patch) = self.patch {
+            write!(f, ".{patch}")?;
+        }
+        Ok(())
+    }
+}
+
+macro_rules! string_serde {
+    ($ty:ty, $expecting:literal) => {
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        // Values are requested as strings so YAML plain scalars such as `0.10` arrive
+        // exactly as written instead of being coerced through a float.
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct StrVisitor;
+
+                impl Visitor<'_> for StrVisitor {
+                    type Value = $ty;
+
+                    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        f.write_str($expecting)
+                    }
+
+                    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                        value.parse().map_err(E::custom)
+                    }
+                }
+
+                deserializer.deserialize_str(StrVisitor)
+            }
+        }
+    };
+}
+
+string_serde!(Date, "a YYYY-MM-DD date");
+string_serde!(Version, "a MAJOR.MINOR or MAJOR.MINOR.PATCH version");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_documented_formats() {
+        assert!("2024-02-29".parse::<Date>().is_ok());
+        assert!("2025-02-29".parse::<Date>().is_err());
+        assert!("2025-13-01".parse::<Date>().is_err());
+        assert!("2025-1-01".parse::<Date>().is_err());
+        assert!("0.2".parse::<Version>().is_ok());
+        assert!("1.2.3".parse::<Version>().is_ok());
+        assert!("1".parse::<Version>().is_err());
+        assert!("01.2".parse::<Version>().is_err());
+        assert!("1.2.3.4".parse::<Version>().is_err());
+        assert!("v1.2".parse::<Version>().is_err());
+    }
+
+    #[test]
+    fn round_trips_and_sorts_numerically() {
+        for value in ["0.1", "0.10", "1.4.2"] {
+            assert_eq!(value.parse::<Version>().unwrap().to_string(), value);
+        }
+        let mut versions = ["0.10", "0.9", "0.2"]
+            .map(|value| value.parse::<Version>().unwrap())
+            .to_vec();
+        versions.sort();
+        assert_eq!(
+            versions.iter().map(Version::to_string).collect::<Vec<_>>(),
+            vec!["0.2", "0.9", "0.10"]
+        );
+
+        let date: Date = serde_yaml_ng::from_str("2025-08-13").unwrap();
+        assert_eq!(serde_json::to_string(&date).unwrap(), "\"2025-08-13\"");
+        assert!(date < "2025-09-01".parse().unwrap());
+    }
+
+    #[test]
+    fn deserializes_yaml_scalars_as_written() {
+        let version: Version = serde_yaml_ng::from_str("0.10").unwrap();
+        assert_eq!(version.minor, 10);
+        let err = serde_yaml_ng::from_str::<Version>("1").unwrap_err();
+        assert!(err.to_string().contains("`1` does not match MAJOR.MINOR"));
+    }
+}
@@ -0,0 +1,547 @@
+//! Assembles the GitHub Pages artifact: the catalogs, the expanded persona and
+//! scenario Markdown, the shared documents in [`PAGES_MANIFEST`], the `static.json`
+//! response headers generated from it, and `index.md`.
+
+use crate::{CatalogError, PersonaSource, RepoConfig, ScenarioSource, SourceCache};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+mod headers;
+mod validate;
+
+pub use headers::{Header, HeaderRule, Redirect, STATIC_CONFIG, StaticConfig, content_type};
+pub use validate::{ArtifactIssue, validate_pages};
+
+/// Artifact folders for the persona and scenario catalogs, wherever the roots are configured.
+const PERSONAS: &str = "personas";
+const SCENARIOS: &str = "scenarios";
+
+/// Where a [`PublishedFile`] is copied from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    /// A file checked into the repository.
+    Repository,
+    /// A file the build already wrote to the artifact, such as a catalog.
+    Artifact,
+}
+
+/// What a [`PublishedFile`] is, which decides its file mode and cache policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileClass {
+    /// Shared instructions and documentation.
+    Document,
+    /// A bootstrap entry point piped straight into `bash`.
+    Script,
+    /// A catalog clients poll for new personas and scenarios.
+    Catalog,
+}
+
+impl FileClass {
+    /// The `Cache-Control` value served for files of this class.
+    pub fn cache_control(self) -> &'static str {
+        match self {
+            // Containers must never bootstrap from a stale script.
+            Self::Script => "no-cache",
+            Self::Catalog => "public, max-age=300",
+            Self::Document => "public, max-age=3600",
+        }
+    }
+
+    /// Whether files of this class are published with mode `0755` rather than `0644`.
+    pub fn executable(self) -> bool {
+        self == Self::Script
+    }
+}
+
+/// One file the artifact publishes besides the persona and scenario trees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublishedFile {
+    /// Path relative to the repository root, or to the artifact for [`Origin::Artifact`].
+    pub source: &'static str,
+    /// Path relative to the artifact root.
+    pub target: &'static str,
+    pub origin: Origin,
+    pub class: FileClass,
+}
+
+impl PublishedFile {
+    const fn document(path: &'static str) -> Self {
+        Self {
+            source: path,
+            target: path,
+            origin: Origin::Repository,
+            class: FileClass::Document,
+        }
+    }
+
+    const fn script(path: &'static str) -> Self {
+        Self {
+            source: path,
+            target: path,
+            origin: Origin::Repository,
+            class: FileClass::Script,
+        }
+    }
+
+    const fn catalog_copy(source: &'static str, target: &'static str) -> Self {
+        Self {
+            source,
+            target,
+            origin: Origin::Artifact,
+            class: FileClass::Catalog,
+        }
+    }
+}
+
+/// Every fixed file in the artifact, in the order it is copied.
+pub const PAGES_MANIFEST: &[PublishedFile] = &[
+    PublishedFile::document("AGENTS.md"),
+    PublishedFile::document("README.md"),
+    PublishedFile::document("docs/INSTRUCTIONS.md"),
+    PublishedFile::document("docs/SPECIFICATION.md"),
+    PublishedFile::script("scripts/BaseInitialization.sh"),
+    PublishedFile::script("scripts/FullInitialization.sh"),
+    PublishedFile::script("scripts/PretaskInitialization.sh"),
+    PublishedFile {
+        source: ".github/workflows/codex-cleanup.yml",
+        target: "workflows/codex-cleanup.yml",
+        origin: Origin::Repository,
+        class: FileClass::Document,
+    },
+    PublishedFile::catalog_copy("personas/catalog.json", "index.json"),
+    PublishedFile::catalog_copy("personas/catalog.json", "personas.json"),
+    PublishedFile::catalog_copy("scenarios/catalog.json", "scenarios/index.json"),
+    PublishedFile::catalog_copy("scenarios/catalog.json", "scenarios.json"),
+];
+
+#[derive(Debug, Error)]
+pub enum PagesError {
+    #[error("refusing to replace {path}: it contains the repository or its sources")]
+    UnsafeOutput { path: PathBuf },
+    #[error("expected file {path} missing")]
+    MissingFile { path: PathBuf },
+    #[error("artifact directory {path} does not exist")]
+    MissingOutput { path: PathBuf },
+    #[error("I/O error at {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error(transparent)]
+    Catalog(#[from] CatalogError),
+}
+
+impl PagesError {
+    fn io(path: &Path, source: io::Error) -> Self {
+        Self::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+/// The artifact written by [`build_pages`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PagesArtifact {
+    pub output: PathBuf,
+    /// Every file written, relative to `output`, in the order it was written.
+    pub files: Vec<PathBuf>,
+}
+
+/// Replaces `output` with a fresh GitHub Pages artifact for the repository at `repo_root`.
+///
+/// The catalogs are generated from the sources in `config` and written only to the
+/// artifact; the checked-in `catalog.json` files are left alone. With a `cache`, sources
+/// unchanged since an earlier build are not parsed again.
+pub fn build_pages(
+    repo_root: &Path,
+    config: &RepoConfig,
+    output: &Path,
+    cache: Option<&mut SourceCache>,
+) -> Result<PagesArtifact, PagesError> {
+    let mut roots = config.persona_roots.iter().chain(&config.scenario_roots);
+    if output.as_os_str().is_empty()
+        || output.parent().is_none()
+        || repo_root.starts_with(output)
+        || roots.any(|root| output.starts_with(root) || root.starts_with(output))
+    {
+        return Err(PagesError::UnsafeOutput {
+            path: output.to_path_buf(),
+        });
+    }
+
+    let options = config.catalog_options();
+    let mut fresh = SourceCache::default();
+    let cache = cache.unwrap_or(&mut fresh);
+    let personas = crate::collect::<PersonaSource>(&config.persona_roots, &options, Some(cache))
+        .into_result()?;
+    let scenarios = crate::collect::<ScenarioSource>(&config.scenario_roots, &options, Some(cache))
+        .into_result()?;
+
+    match fs::remove_dir_all(output) {
+        Ok(()) => {}
+        Err(source) if source.kind() == io::ErrorKind::NotFound => {}
+        Err(source) => return Err(PagesError::io(output, source)),
+    }
+    let mut artifact = Artifact {
+        output,
+        files: Vec::new(),
+    };
+
+    let entries = personas.iter().map(|source| source.entry.clone()).collect();
+    let index = crate::build_index(
+        config.personas_dir(),
+        &config.agents_path,
+        entries,
+        &options,
+    )?;
+    artifact.write_json(&Path::new(PERSONAS).join("catalog.json"), &index)?;
+    let entries = scenarios
+        .iter()
+        .map(|source| source.entry.clone())
+        .collect();
+    let index = crate::build_scenario_index(
+        config.scenarios_dir(),
+        &config.agents_path,
+        entries,
+        &options,
+    )?;
+    artifact.write_json(&Path::new(SCENARIOS).join("catalog.json"), &index)?;
+
+    let persona_links = artifact.publish(personas.iter().map(|source| {
+        Ok(Page {
+            roots: &config.persona_roots,
+            root: &source.entry.root,
+            path: &source.path,
+            markdown: source.published_markdown()?,
+        })
+    }))?;
+    let scenario_links = artifact.publish(scenarios.iter().map(|source| {
+        Ok(Page {
+            roots: &config.scenario_roots,
+            root: &source.entry.root,
+            path: &source.path,
+            markdown: source.published_markdown()?,
+        })
+    }))?;
+
+    for file in PAGES_MANIFEST {
+        let source = match file.origin {
+            Origin::Repository => repo_root.join(file.source),
+            Origin::Artifact => output.join(file.source),
+        };
+        artifact.copy(&source, Path::new(file.target), file.class.executable())?;
+    }
+    let headers = StaticConfig::from_manifest(PAGES_MANIFEST);
+    artifact.write_json(Path::new(STATIC_CONFIG), &headers)?;
+
+    let sections = [("Personas", persona_links), ("Scenarios", scenario_links)];
+    let landing = landing_page(repo_root, config, &sections)?;
+    artifact.write(Path::new("index.md"), landing)?;
+    // An empty `.nojekyll` disables Jekyll processing on GitHub Pages.
+    artifact.write(Path::new(".nojekyll"), String::new())?;
+
+    Ok(PagesArtifact {
+        output: output.to_path_buf(),
+        files: artifact.files,
+    })
+}
+
+/// A published page's name without its extension, e.g. `security/AUDITOR`, and its artifact path.
+type Link = (PathBuf, PathBuf);
+
+/// A collected persona or scenario, published at the path its catalog `uri` names.
+struct Page<'a> {
+    /// The roots of its kind; `path` lies below one of them.
+    roots: &'a [PathBuf],
+    /// The repository-relative name of that root, as recorded in the catalog entry.
+    root: &'a str,
+    path: &'a Path,
+    /// The published Markdown.
+    markdown: String,
+}
+
+impl Page<'_> {
+    /// The path below its root, e.g. `security/AUDITOR.md`.
+    fn name(&self) -> PathBuf {
+        let root = self
+            .roots
+            .iter()
+            .rev()
+            .find(|root| self.path.starts_with(root))
+            .map_or(self.path, PathBuf::as_path);
+        relative(root, self.path)
+    }
+
+    /// The artifact path, which is also the entry's `uri` below the site root.
+    fn target(&self) -> PathBuf {
+        Path::new(self.root).join(self.name())
+    }
+}
+
+/// `index.md`: `AGENTS.md`, a titled list of links per section, then `docs/INSTRUCTIONS.md`.
+fn landing_page(
+    repo_root: &Path,
+    config: &RepoConfig,
+    sections: &[(&str, Vec<Link>)],
+) -> Result<String, PagesError> {
+    let mut page = read(&config.agents_path)?;
+    for (title, links) in sections {
+        page.push_str(&format!("\n## {title}\n"));
+        for (name, link) in links {
+            page.push_str(&format!("- [{}]({})\n", url_path(name), url_path(link)));
+        }
+    }
+    page.push('\n');
+    page.push_str(&read(&repo_root.join("docs").join("INSTRUCTIONS.md"))?);
+    Ok(page)
+}
+
+/// Writes files below the artifact root and records them.
+struct Artifact<'a> {
+    output: &'a Path,
+    files: Vec<PathBuf>,
+}
+
+impl Artifact<'_> {
+    fn write(&mut self, target: &Path, contents: String) -> Result<(), PagesError> {
+        let path = self.prepare(target)?;
+        fs::write(&path, contents).map_err(|source| PagesError::io(&path, source))?;
+        self.record(target);
+        Ok(())
+    }
+
+    fn write_json<T: serde::Serialize>(
+        &mut self,
+        target: &Path,
+        value: &T,
+    ) -> Result<(), PagesError> {
+        let path = self.output.join(target);
+        let mut json = serde_json::to_string_pretty(value)
+            .map_err(|source| CatalogError::json(&path, source))?;
+        json.push('\n');
+        self.write(target, json)
+    }
+
+    fn copy(&mut self, source: &Path, target: &Path, executable: bool) -> Result<(), PagesError> {
+        if !source.is_file() {
+            return Err(PagesError::MissingFile {
+                path: source.to_path_buf(),
+            });
+        }
+        let path = self.prepare(target)?;
+        fs::copy(source, &path).map_err(|err| PagesError::io(source, err))?;
+        set_mode(&path, executable)?;
+        self.record(target);
+        Ok(())
+    }
+
+    /// Writes the published Markdown of every page and returns their links, sorted by name.
+    fn publish<'p>(
+        &mut self,
+        pages: impl Iterator<Item = Result<Page<'p>, CatalogError>>,
+    ) -> Result<Vec<Link>, PagesError> {
+        let mut links = Vec::new();
+        for page in pages {
+            let page = page?;
+            let target = page.target();
+            let name = page.name().with_extension("");
+            self.write(&target, page.markdown)?;
+            links.push((name, target));
+        }
+        links.sort();
+        Ok(links)
+    }
+
+    fn prepare(&self, target: &Path) -> Result<PathBuf, PagesError> {
+        let path = self.output.join(target);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|source| PagesError::io(parent, source))?;
+        }
+        Ok(path)
+    }
+
+    /// Records `target` once; later steps may overwrite a copied file.
+    fn record(&mut self, target: &Path) {
+        if !self.files.iter().any(|file| file == target) {
+            self.files.push(target.to_path_buf());
+        }
+    }
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, executable: bool) -> Result<(), PagesError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = if executable { 0o755 } else { 0o644 };
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+        .map_err(|source| PagesError::io(path, source))
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _executable: bool) -> Result<(), PagesError> {
+    Ok(())
+}
+
+fn read(path: &Path) -> Result<String, PagesError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(source) if source.kind() == io::ErrorKind::NotFound => Err(PagesError::MissingFile {
+            path: path.to_path_buf(),
+        }),
+        Err(source) => Err(PagesError::io(path, source)),
+    }
+}
+
+fn relative(base: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(base).unwrap_or(path).to_path_buf()
+}
+
+fn url_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().expect("parent")).expect("dir");
+        fs::write(path, contents).expect("write");
+    }
+
+    fn write_repo(root: &Path) {
+        for file in PAGES_MANIFEST {
+            if file.origin == Origin::Repository {
+                write(root, file.source, &format!("{}\n", file.source));
+            }
+        }
+        write(root, "AGENTS.md", "# Agents\n");
+        write(root, "docs/INSTRUCTIONS.md", "# Instructions\n");
+        write(root, "snippets/loop.md", "- Run tests.\n");
+        write(
+            root,
+            "personas/ONE.md",
+            "---\nid: one\nname: One\n---\n# One\n{{> snippets/loop.md}}\n",
+        );
+        write(
+            root,
+            "personas/security/AUDITOR.md",
+            "---\nid: auditor\nname: Auditor\n---\n# Auditor\n",
+        );
+        write(root, "personas/catalog.json", "stale");
+        write(root, "personas/.draft/NOTES.md", "draft");
+        write(
+            root,
+            "scenarios/AUDIT.md",
+            "---\nid: audit\nname: Audit\n---\n# Audit\n",
+        );
+    }
+
+    #[test]
+    fn builds_the_artifact_from_the_manifest() {
+        let tmp = tempdir().expect("tempdir");
+        let repo = tmp.path().join("repo");
+        write_repo(&repo);
+        let output = tmp.path().join("public");
+        write(&output, "obsolete.txt", "old");
+        let config = RepoConfig::defaults(&repo);
+
+        let artifact = build_pages(&repo, &config, &output, None).expect("build");
+
+        for file in PAGES_MANIFEST {
+            assert!(output.join(file.target).is_file(), "{}", file.target);
+        }
+        assert!(!output.join("obsolete.txt").exists());
+        assert!(!output.join("personas/.draft").exists());
+        assert_eq!(
+            fs::read_to_string(output.join("personas/ONE.md")).expect("persona"),
+            "---\nid: one\nname: One\n---\n# One\n- Run tests.\n"
+        );
+        let catalog = fs::read_to_string(output.join("personas.json")).expect("catalog");
+        assert_eq!(
+            catalog,
+            fs::read_to_string(output.join("personas/catalog.json")).expect("catalog")
+        );
+        let index: crate::Index = serde_json::from_str(&catalog).expect("index");
+        assert_eq!(index.personas.len(), 2);
+        assert_eq!(
+            fs::read_to_string(repo.join("personas/catalog.json")).expect("source catalog"),
+            "stale"
+        );
+        let scenarios: crate::ScenarioIndex =
+            serde_json::from_str(&fs::read_to_string(output.join("scenarios.json")).expect("json"))
+                .expect("scenario index");
+        assert_eq!(scenarios.scenarios[0].meta.id, "audit");
+
+        assert_eq!(
+            fs::read_to_string(output.join("index.md")).expect("landing page"),
+            "# Agents\n\n## Personas\n- [ONE](personas/ONE.md)\n- [security/AUDITOR](personas/security/AUDITOR.md)\n\n## Scenarios\n- [AUDIT](scenarios/AUDIT.md)\n\n# Instructions\n"
+        );
+        assert!(output.join(".nojekyll").is_file());
+        let headers: StaticConfig =
+            serde_json::from_str(&fs::read_to_string(output.join(STATIC_CONFIG)).expect("read"))
+                .expect("static config");
+        assert_eq!(headers, StaticConfig::from_manifest(PAGES_MANIFEST));
+        assert!(
+            artifact
+                .files
+                .contains(&PathBuf::from("workflows/codex-cleanup.yml"))
+        );
+        assert_eq!(
+            artifact
+                .files
+                .iter()
+                .filter(|file| file.ends_with("personas/ONE.md"))
+                .count(),
+            1
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn publishes_scripts_as_executables() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempdir().expect("tempdir");
+        write_repo(tmp.path());
+        let output = tmp.path().join("public");
+        build_pages(tmp.path(), &RepoConfig::defaults(tmp.path()), &output, None).expect("build");
+
+        let mode = |path: &str| {
+            fs::metadata(output.join(path))
+                .expect("metadata")
+                .permissions()
+                .mode()
+                & 0o777
+        };
+        assert_eq!(mode("scripts/FullInitialization.sh"), 0o755);
+        assert_eq!(mode("AGENTS.md"), 0o644);
+    }
+
+    #[test]
+    fn reports_missing_files_and_refuses_unsafe_outputs() {
+        let tmp = tempdir().expect("tempdir");
+        write_repo(tmp.path());
+        let config = RepoConfig::defaults(tmp.path());
+
+        for output in [
+            tmp.path().to_path_buf(),
+            tmp.path().join("personas").join("public"),
+            PathBuf::from("/"),
+        ] {
+            let err = build_pages(tmp.path(), &config, &output, None).unwrap_err();
+            assert!(matches!(err, PagesError::UnsafeOutput { .. }), "{err}");
+        }
+        assert!(tmp.path().join("personas").join("ONE.md").is_file());
+
+        fs::remove_file(tmp.path().join("README.md")).expect("remove");
+        let err = build_pages(tmp.path(), &config, &tmp.path().join("public"), None).unwrap_err();
+        assert!(err.to_string().contains("README.md missing"), "{err}");
+    }
+}
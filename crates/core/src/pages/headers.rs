@@ -0,0 +1,165 @@
+use super::{Origin, PublishedFile};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// The artifact file that tells the static host which response headers to send.
+pub const STATIC_CONFIG: &str = "static.json";
+
+/// The contents of [`STATIC_CONFIG`].
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct StaticConfig {
+    #[serde(default)]
+    pub headers: Vec<HeaderRule>,
+    #[serde(default)]
+    pub redirects: Vec<Redirect>,
+}
+
+/// Response headers for the file at `source`, a site path such as `/scripts/FullInitialization.sh`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct HeaderRule {
+    pub source: String,
+    pub headers: Vec<Header>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Header {
+    pub key: String,
+    pub value: String,
+}
+
+/// Sends requests for `source` to `destination`; both are site paths.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Redirect {
+    pub source: String,
+    pub destination: String,
+}
+
+impl StaticConfig {
+    /// One rule per published file: its class decides `Cache-Control`, its extension
+    /// `Content-Type`. Files the manifest copies from the artifact, such as
+    /// `personas/catalog.json`, are published too and get a rule of their own.
+    pub fn from_manifest(manifest: &[PublishedFile]) -> Self {
+        let mut headers: Vec<HeaderRule> = Vec::new();
+        for file in manifest {
+            let written = (file.origin == Origin::Artifact).then_some(file.source);
+            for path in [Some(file.target), written].into_iter().flatten() {
+                let source = format!("/{path}");
+                if headers.iter().any(|rule| rule.source == source) {
+                    continue;
+                }
+                headers.push(HeaderRule {
+                    source,
+                    headers: vec![
+                        Header {
+                            key: "Cache-Control".to_string(),
+                            value: file.class.cache_control().to_string(),
+                        },
+                        Header {
+                            key: "Content-Type".to_string(),
+                            value: content_type(Path::new(path)).to_string(),
+                        },
+                    ],
+                });
+            }
+        }
+        Self {
+            headers,
+            redirects: Vec::new(),
+        }
+    }
+
+    /// The headers configured for the site path `path`, if any rule names it.
+    pub fn headers_for(&self, path: &str) -> &[Header] {
+        let path = format!("/{}", path.trim_start_matches('/'));
+        self.headers
+            .iter()
+            .find(|rule| rule.source == path)
+            .map_or(&[], |rule| rule.headers.as_slice())
+    }
+}
+
+/// The `Content-Type` served for `path`, chosen by its extension.
+///
+/// Scripts are served as plain text so browsers display them and `curl | bash` works.
+pub fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("md") => "text/markdown; charset=utf-8",
+        Some("json") => "application/json; charset=utf-8",
+        Some("sh" | "txt") => "text/plain; charset=utf-8",
+        Some("yml" | "yaml") => "text/yaml; charset=utf-8",
+        Some("html") => "text/html; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::PAGES_MANIFEST;
+    use super::*;
+
+    #[test]
+    fn generates_a_rule_per_manifest_file() {
+        let config = StaticConfig::from_manifest(PAGES_MANIFEST);
+        // The manifest's files plus the two catalogs its copies are made from.
+        assert_eq!(config.headers.len(), PAGES_MANIFEST.len() + 2);
+
+        let script = config.headers_for("scripts/FullInitialization.sh");
+        assert_eq!(
+            script,
+            [
+                Header {
+                    key: "Cache-Control".into(),
+                    value: "no-cache".into()
+                },
+                Header {
+                    key: "Content-Type".into(),
+                    value: "text/plain; charset=utf-8".into()
+                },
+            ]
+        );
+        assert_eq!(
+            config.headers_for("/personas.json")[1].value,
+            "application/json; charset=utf-8"
+        );
+        assert_eq!(
+            config.headers_for("/workflows/codex-cleanup.yml")[0].value,
+            "public, max-age=3600"
+        );
+        for catalog in ["personas/catalog.json", "scenarios/catalog.json"] {
+            let headers = config.headers_for(catalog);
+            assert_eq!(headers[0].value, "public, max-age=300");
+            assert_eq!(headers[1].value, "application/json; charset=utf-8");
+        }
+        assert!(config.headers_for("/personas/ONE.md").is_empty());
+    }
+
+    #[test]
+    fn round_trips_the_published_format() {
+        let json = r#"{
+  "headers": [
+    {
+      "source": "/scripts/BaseInitialization.sh",
+      "headers": [
+        {
+          "key": "Cache-Control",
+          "value": "no-cache"
+        }
+      ]
+    }
+  ],
+  "redirects": []
+}"#;
+        let config: StaticConfig = serde_json::from_str(json).expect("static config");
+        assert_eq!(config.headers[0].source, "/scripts/BaseInitialization.sh");
+        assert_eq!(serde_json::to_string_pretty(&config).expect("json"), json);
+        assert!(serde_json::from_str::<StaticConfig>(r#"{"header": []}"#).is_err());
+    }
+}
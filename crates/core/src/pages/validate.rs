@@ -0,0 +1,402 @@
+use super::{Origin, PAGES_MANIFEST, PagesError, STATIC_CONFIG, StaticConfig};
+use crate::{Index, ScenarioIndex};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Component, Path};
+use thiserror::Error;
+
+/// Generated files every artifact carries besides the [`PAGES_MANIFEST`] targets.
+const GENERATED_PATHS: &[&str] = &[
+    "index.md",
+    STATIC_CONFIG,
+    "personas/catalog.json",
+    "scenarios/catalog.json",
+];
+
+/// Helpers that were published once and must not come back.
+const LEGACY_PATHS: &[&str] = &[
+    "scripts/split-initialization-cached-base.sh",
+    "scripts/full-initialization.sh",
+    "scripts/split-initialization-pretask.sh",
+    "scripts/init-container.sh",
+    "scripts/init-ephemeral-container.sh",
+    "scripts/pre-task.sh",
+    "scripts/lib/container-bootstrap-common.sh",
+    "scripts/agent-sync.sh",
+    "scripts/repo-setup.sh",
+];
+
+/// One problem found in a built artifact. Paths are relative to the artifact root.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ArtifactIssue {
+    #[error("{path} is missing or empty")]
+    Missing { path: String },
+    #[error("{path} is a legacy helper and must not be published")]
+    Legacy { path: String },
+    #[error("{path} is not a valid catalog: {message}")]
+    InvalidCatalog { path: String, message: String },
+    #[error("{copy} differs from {original}")]
+    CopyMismatch { copy: String, original: String },
+    #[error("{catalog}: base_uri `{base_uri}` names no file in the artifact")]
+    MissingBaseUri { catalog: String, base_uri: String },
+    #[error("{catalog}: `{id}` has uri {uri}, which is not under {base_url}")]
+    ForeignUri {
+        catalog: String,
+        id: String,
+        uri: String,
+        base_url: String,
+    },
+    #[error("{catalog}: `{id}` has uri {uri}, but {path} is not in the artifact")]
+    UnresolvedUri {
+        catalog: String,
+        id: String,
+        uri: String,
+        path: String,
+    },
+    #[error("static.json is not valid: {message}")]
+    InvalidStaticConfig { message: String },
+    #[error("static.json sets headers for {pattern}, which names no file in the artifact")]
+    MissingHeaderSource { pattern: String },
+    #[error(
+        "static.json redirects {pattern} to {destination}, which names no file in the artifact"
+    )]
+    MissingRedirectTarget {
+        pattern: String,
+        destination: String,
+    },
+}
+
+/// Checks the artifact in `output`, whose catalogs publish URIs under `base_url`.
+///
+/// Returns every issue found; an empty list means the artifact is ready to deploy.
+pub fn validate_pages(output: &Path, base_url: &str) -> Result<Vec<ArtifactIssue>, PagesError> {
+    if !output.is_dir() {
+        return Err(PagesError::MissingOutput {
+            path: output.to_path_buf(),
+        });
+    }
+    let mut issues = Vec::new();
+
+    let required = PAGES_MANIFEST
+        .iter()
+        .map(|file| file.target)
+        .chain(GENERATED_PATHS.iter().copied());
+    for path in required {
+        let non_empty = fs::metadata(output.join(path)).is_ok_and(|meta| meta.len() > 0);
+        if !non_empty {
+            issues.push(ArtifactIssue::Missing {
+                path: path.to_string(),
+            });
+        }
+    }
+    for path in LEGACY_PATHS {
+        if output.join(path).exists() {
+            issues.push(ArtifactIssue::Legacy {
+                path: path.to_string(),
+            });
+        }
+    }
+
+    // Every catalog and catalog copy, parsed once so each broken file is reported once.
+    let mut catalogs = BTreeMap::new();
+    for file in PAGES_MANIFEST
+        .iter()
+        .filter(|file| file.origin == Origin::Artifact)
+    {
+        for path in [file.source, file.target] {
+            if !catalogs.contains_key(path) {
+                let value = match read_json(output, path)? {
+                    Parsed::Valid(value) => Some(value),
+                    Parsed::Missing => None,
+                    Parsed::Invalid(message) => {
+                        issues.push(ArtifactIssue::InvalidCatalog {
+                            path: path.to_string(),
+                            message,
+                        });
+                        None
+                    }
+                };
+                catalogs.insert(path, value);
+            }
+        }
+        if let (Some(Some(original)), Some(Some(copy))) =
+            (catalogs.get(file.source), catalogs.get(file.target))
+            && original != copy
+        {
+            issues.push(ArtifactIssue::CopyMismatch {
+                copy: file.target.to_string(),
+                original: file.source.to_string(),
+            });
+        }
+    }
+
+    let site = Site { output, base_url };
+    let catalog = |path: &str| catalogs.get(path).cloned().flatten();
+    if let Some(index) = typed::<Index>("personas.json", catalog("personas.json"), &mut issues) {
+        let entries = index
+            .personas
+            .iter()
+            .map(|entry| (entry.meta.id.as_str(), entry.uri.as_str()));
+        site.check_catalog("personas.json", &index.base_uri, entries, &mut issues);
+    }
+    if let Some(index) =
+        typed::<ScenarioIndex>("scenarios.json", catalog("scenarios.json"), &mut issues)
+    {
+        let entries = index
+            .scenarios
+            .iter()
+            .map(|entry| (entry.meta.id.as_str(), entry.uri.as_str()));
+        site.check_catalog("scenarios.json", &index.base_uri, entries, &mut issues);
+    }
+
+    match read_json(output, STATIC_CONFIG)? {
+        Parsed::Valid(value) => match serde_json::from_value::<StaticConfig>(value) {
+            Ok(config) => {
+                for rule in config.headers {
+                    if !site.contains(&rule.source) {
+                        issues.push(ArtifactIssue::MissingHeaderSource {
+                            pattern: rule.source,
+                        });
+                    }
+                }
+                for redirect in config.redirects {
+                    if !site.contains(&redirect.destination) {
+                        issues.push(ArtifactIssue::MissingRedirectTarget {
+                            pattern: redirect.source,
+                            destination: redirect.destination,
+                        });
+                    }
+                }
+            }
+            Err(err) => issues.push(ArtifactIssue::InvalidStaticConfig {
+                message: err.to_string(),
+            }),
+        },
+        Parsed::Invalid(message) => issues.push(ArtifactIssue::InvalidStaticConfig { message }),
+        Parsed::Missing => {}
+    }
+
+    Ok(issues)
+}
+
+/// A JSON file read from the artifact.
+enum Parsed {
+    /// The required-file check already reports missing files.
+    Missing,
+    Invalid(String),
+    Valid(Value),
+}
+
+fn read_json(output: &Path, path: &str) -> Result<Parsed, PagesError> {
+    let full = output.join(path);
+    let contents = match fs::read_to_string(&full) {
+        Ok(contents) => contents,
+        Err(source) if source.kind() == io::ErrorKind::NotFound => return Ok(Parsed::Missing),
+        Err(source) => return Err(PagesError::io(&full, source)),
+    };
+    Ok(match serde_json::from_str(&contents) {
+        Ok(value) => Parsed::Valid(value),
+        Err(err) => Parsed::Invalid(err.to_string()),
+    })
+}
+
+/// Deserializes a parsed catalog, recording a schema mismatch in `issues`.
+fn typed<T: DeserializeOwned>(
+    path: &str,
+    value: Option<Value>,
+    issues: &mut Vec<ArtifactIssue>,
+) -> Option<T> {
+    match serde_json::from_value(value?) {
+        Ok(catalog) => Some(catalog),
+        Err(err) => {
+            issues.push(ArtifactIssue::InvalidCatalog {
+                path: path.to_string(),
+                message: err.to_string(),
+            });
+            None
+        }
+    }
+}
+
+/// The artifact as served from `base_url`.
+struct Site<'a> {
+    output: &'a Path,
+    base_url: &'a str,
+}
+
+impl Site<'_> {
+    fn check_catalog<'e>(
+        &self,
+        catalog: &str,
+        base_uri: &str,
+        entries: impl Iterator<Item = (&'e str, &'e str)>,
+        issues: &mut Vec<ArtifactIssue>,
+    ) {
+        if !self.contains(base_uri) {
+            issues.push(ArtifactIssue::MissingBaseUri {
+                catalog: catalog.to_string(),
+                base_uri: base_uri.to_string(),
+            });
+        }
+        let base_url = self.base_url.trim_end_matches('/');
+        for (id, uri) in entries {
+            let Some(path) = uri
+                .strip_prefix(base_url)
+                .and_then(|rest| rest.strip_prefix('/'))
+            else {
+                issues.push(ArtifactIssue::ForeignUri {
+                    catalog: catalog.to_string(),
+                    id: id.to_string(),
+                    uri: uri.to_string(),
+                    base_url: base_url.to_string(),
+                });
+                continue;
+            };
+            if !self.contains(path) {
+                issues.push(ArtifactIssue::UnresolvedUri {
+                    catalog: catalog.to_string(),
+                    id: id.to_string(),
+                    uri: uri.to_string(),
+                    path: path.to_string(),
+                });
+            }
+        }
+    }
+
+    /// Whether the site path `path` (with or without a leading `/`) is a file in the artifact.
+    fn contains(&self, path: &str) -> bool {
+        let relative = Path::new(path.trim_start_matches('/'));
+        let inside = relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+        inside && self.output.join(relative).is_file()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::build_pages;
+    use super::*;
+    use crate::RepoConfig;
+    use tempfile::tempdir;
+
+    const BASE_URL: &str = "https://example.test/site";
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().expect("parent")).expect("dir");
+        fs::write(path, contents).expect("write");
+    }
+
+    /// Builds an artifact from a repository holding every manifest file.
+    fn build(root: &Path) -> std::path::PathBuf {
+        let repo = root.join("repo");
+        for file in PAGES_MANIFEST {
+            if file.origin == Origin::Repository {
+                write(&repo, file.source, "content\n");
+            }
+        }
+        write(
+            &repo,
+            "personas/ONE.md",
+            "---\nid: one\nname: One\n---\n# One\n",
+        );
+        write(
+            &repo,
+            "scenarios/AUDIT.md",
+            "---\nid: audit\nname: Audit\n---\n# Audit\n",
+        );
+        let mut config = RepoConfig::defaults(&repo);
+        config.pages_base_url = BASE_URL.to_string();
+        let output = root.join("public");
+        build_pages(&repo, &config, &output, None).expect("build");
+        output
+    }
+
+    #[test]
+    fn accepts_a_fresh_build() {
+        let tmp = tempdir().expect("tempdir");
+        let output = build(tmp.path());
+        assert_eq!(validate_pages(&output, BASE_URL).expect("validate"), []);
+        assert_eq!(
+            validate_pages(&output, &format!("{BASE_URL}/")).expect("validate"),
+            []
+        );
+    }
+
+    #[test]
+    fn reports_unresolved_uris_and_mismatched_copies() {
+        let tmp = tempdir().expect("tempdir");
+        let output = build(tmp.path());
+        fs::remove_file(output.join("personas/ONE.md")).expect("remove persona");
+        write(
+            &output,
+            "index.json",
+            r#"{"base_uri": "AGENTS.md", "personas": []}"#,
+        );
+        write(&output, "scripts/agent-sync.sh", "legacy\n");
+        fs::remove_file(output.join("scripts/FullInitialization.sh")).expect("remove script");
+
+        let issues = validate_pages(&output, BASE_URL).expect("validate");
+        let messages = issues.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                "scripts/FullInitialization.sh is missing or empty",
+                "scripts/agent-sync.sh is a legacy helper and must not be published",
+                "index.json differs from personas/catalog.json",
+                "personas.json: `one` has uri https://example.test/site/personas/ONE.md, but personas/ONE.md is not in the artifact",
+                "static.json sets headers for /scripts/FullInitialization.sh, which names no file in the artifact",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_invalid_catalogs_and_foreign_uris() {
+        let tmp = tempdir().expect("tempdir");
+        let output = build(tmp.path());
+        write(&output, "scenarios.json", r#"{"scenarios": []}"#);
+        fs::remove_file(output.join("AGENTS.md")).expect("remove agents");
+
+        let issues = validate_pages(&output, "https://elsewhere.test").expect("validate");
+        assert!(issues.contains(&ArtifactIssue::Missing {
+            path: "AGENTS.md".into()
+        }));
+        assert!(issues.contains(&ArtifactIssue::MissingBaseUri {
+            catalog: "personas.json".into(),
+            base_uri: "AGENTS.md".into()
+        }));
+        assert!(issues.iter().any(|issue| matches!(
+            issue,
+            ArtifactIssue::ForeignUri { id, .. } if id == "one"
+        )));
+        assert!(issues.iter().any(|issue| matches!(
+            issue,
+            ArtifactIssue::InvalidCatalog { path, message }
+                if path == "scenarios.json" && message.contains("base_uri")
+        )));
+
+        write(
+            &output,
+            "static.json",
+            r#"{"redirects": [{"source": "/old.md", "destination": "/new.md"}]}"#,
+        );
+        let issues = validate_pages(&output, BASE_URL).expect("validate");
+        assert!(issues.iter().any(|issue| matches!(
+            issue,
+            ArtifactIssue::MissingRedirectTarget { destination, .. } if destination == "/new.md"
+        )));
+
+        write(&output, "static.json", "{");
+        let issues = validate_pages(&output, BASE_URL).expect("validate");
+        assert!(
+            issues
+                .iter()
+                .any(|issue| matches!(issue, ArtifactIssue::InvalidStaticConfig { .. }))
+        );
+        assert!(validate_pages(&tmp.path().join("missing"), BASE_URL).is_err());
+    }
+}
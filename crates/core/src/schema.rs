@@ -0,0 +1,246 @@
+use crate::SourceSpan;
+use std::fmt;
+
+/// Front matter keys defined by SPECIFICATION §3.1 and §4.1.
+pub const META_FIELDS: &[&str] = &[
+    "id",
+    "name",
+    "description",
+    "tags",
+    "author",
+    "created_at",
+    "version",
+];
+
+/// Persona front matter keys: [`META_FIELDS`] plus `extends` (SPECIFICATION §3.1).
+pub const PERSONA_FIELDS: &[&str] = &concat::<{ META_FIELDS.len() + 1 }>(META_FIELDS, &["extends"]);
+
+/// `first` followed by `second`, for building one field list from another.
+const fn concat<const N: usize>(
+    first: &[&'static str],
+    second: &[&'static str],
+) -> [&'static str; N] {
+    assert!(first.len() + second.len() == N);
+    let mut fields = [""; N];
+    let mut index = 0;
+    while index < first.len() {
+        fields[index] = first[index];
+        index += 1;
+    }
+    while index < N {
+        fields[index] = second[index - first.len()];
+        index += 1;
+    }
+    fields
+}
+
+/// Keys the catalog generator writes itself, so they cannot be set in front matter.
+pub const RESERVED_FIELDS: &[&str] = &["uri", "root", "handoffs"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaIssueKind {
+    UnknownField {
+        field: String,
+        suggestion: Option<String>,
+    },
+    ReservedField {
+        field: String,
+    },
+}
+
+impl fmt::Display for SchemaIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaIssueKind::UnknownField {
+                field,
+                suggestion: Some(suggestion),
+            } => write!(f, "unknown field `{field}`; did you mean `{suggestion}`?"),
+            SchemaIssueKind::UnknownField {
+                field,
+                suggestion: None,
+            } => write!(f, "unknown field `{field}`"),
+            SchemaIssueKind::ReservedField { field } => write!(
+                f,
+                "`{field}` is generated by the catalog and cannot be set in front matter"
+            ),
+        }
+    }
+}
+
+/// A strict-mode front matter problem, located in the original file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaIssue {
+    pub span: SourceSpan,
+    pub kind: SchemaIssueKind,
+}
+
+impl fmt::Display for SchemaIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.span.line, self.kind)
+    }
+}
+
+/// Validates front matter keys against `fields` for strict mode.
+///
+/// Reserved keys are always reported, and so is every other key that is neither in
+/// `fields` nor in `custom_fields`, with the closest known field as a suggestion when
+/// one is near. Keys are read from the raw file text so each issue points at the line
+/// that introduced it.
+pub fn check_front_matter_schema(
+    content: &str,
+    fields: &[&str],
+    custom_fields: &[String],
+) -> Vec<SchemaIssue> {
+    let mut issues = check_reserved_fields(content);
+    let unknown = front_matter_keys(content)
+        .into_iter()
+        .filter(|(_, key)| {
+            !fields.contains(key)
+                && !RESERVED_FIELDS.contains(key)
+                && !custom_fields.iter().any(|field| field == key)
+        })
+        .map(|(line, key)| SchemaIssue {
+            span: SourceSpan::at(content, line, 1),
+            kind: SchemaIssueKind::UnknownField {
+                field: key.to_string(),
+                suggestion: suggest(key, fields).map(str::to_string),
+            },
+        });
+    issues.extend(unknown);
+    issues.sort_by_key(|issue| issue.span.line);
+    issues
+}
+
+/// Reports custom fields that would collide with keys in the generated catalog entry.
+pub fn check_reserved_fields(content: &str) -> Vec<SchemaIssue> {
+    front_matter_keys(content)
+        .into_iter()
+        .filter(|(_, key)| RESERVED_FIELDS.contains(key))
+        .map(|(line, key)| SchemaIssue {
+            span: SourceSpan::at(content, line, 1),
+            kind: SchemaIssueKind::ReservedField {
+                field: key.to_string(),
+            },
+        })
+        .collect()
+}
+
+/// Yields `(line, key)` for every top-level key in the front matter.
+fn front_matter_keys(content: &str) -> Vec<(usize, &str)> {
+    let mut keys = Vec::new();
+    for (index, line) in content.lines().enumerate().skip(1) {
+        let line = line.trim_end_matches('\r');
+        if line.starts_with("---") {
+            break;
+        }
+        if line.starts_with([' ', '\t', '#', '-']) {
+            continue;
+        }
+        let Some((key, _)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim_end();
+        if !key.is_empty() {
+            keys.push((index + 1, key));
+        }
+    }
+    keys
+}
+
+fn suggest<'a>(key: &str, fields: &[&'a str]) -> Option<&'a str> {
+    let threshold = (key.chars().count() / 3).max(1);
+    fields
+        .iter()
+        .map(|field| (edit_distance(key, field), *field))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, field)| field)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggests_close_field_names_and_rejects_unlisted_ones() {
+        let content =
+            "---\nid: one\nname: One\ndescripton: typo\ntag: [a]\nmodel_hints: x\nfoo: 1\n---\n";
+        let issues = check_front_matter_schema(content, META_FIELDS, &[]);
+        let messages = issues
+            .iter()
+            .map(|issue| (issue.span.line, issue.kind.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    4,
+                    "unknown field `descripton`; did you mean `description`?".to_string()
+                ),
+                (5, "unknown field `tag`; did you mean `tags`?".to_string()),
+                (6, "unknown field `model_hints`".to_string()),
+                (7, "unknown field `foo`".to_string()),
+            ]
+        );
+
+        let allowed = ["model_hints".to_string(), "foo".to_string()];
+        assert_eq!(
+            check_front_matter_schema(content, META_FIELDS, &allowed).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn persona_fields_extend_the_shared_fields() {
+        assert_eq!(PERSONA_FIELDS[..META_FIELDS.len()], *META_FIELDS);
+        assert_eq!(PERSONA_FIELDS[META_FIELDS.len()..], ["extends"]);
+    }
+
+    #[test]
+    fn rejects_reserved_fields() {
+        let content = "---\nid: one\nuri: https://example.invalid\n---\n";
+        let issues = check_reserved_fields(content);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].span.line, 3);
+        assert!(issues[0].kind.to_string().contains("`uri` is generated"));
+    }
+
+    #[test]
+    fn schema_check_reports_reserved_fields_without_suggestions() {
+        let content = "---\nid: one\nroot: custom\nnam: One\nhandoffs: []\n---\n";
+        let messages = check_front_matter_schema(content, META_FIELDS, &[])
+            .iter()
+            .map(|issue| (issue.span.line, issue.kind.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    3,
+                    "`root` is generated by the catalog and cannot be set in front matter"
+                        .to_string()
+                ),
+                (4, "unknown field `nam`; did you mean `name`?".to_string()),
+                (
+                    5,
+                    "`handoffs` is generated by the catalog and cannot be set in front matter"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+}
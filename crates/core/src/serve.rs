@@ -0,0 +1,460 @@
+//! A local HTTP preview of the published site, so clients and bootstrap scripts can be
+//! tested against `PAGES_BASE_URL=http://127.0.0.1:<port>` without network access.
+
+use crate::cache::Snapshot;
+use crate::{
+    Origin, PAGES_MANIFEST, RepoConfig, STATIC_CONFIG, SourceCache, StaticConfig, build_pages,
+    content_type,
+};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, PoisonError, mpsc};
+use std::thread;
+use std::time::Duration;
+
+/// How long a connection may stall while sending its request or receiving the response.
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What a [`PreviewServer`] serves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SiteRoot {
+    /// A built Pages artifact, served as is.
+    Artifact(PathBuf),
+    /// The repository itself, rebuilt into `output` when a source changed since the last
+    /// request so edits show up on reload. Catalog URIs point at the server.
+    Live {
+        repo_root: PathBuf,
+        config: Box<RepoConfig>,
+        output: PathBuf,
+    },
+}
+
+impl SiteRoot {
+    /// A live preview of `repo_root`, built under its `target` directory.
+    pub fn live(repo_root: &Path, config: RepoConfig) -> Self {
+        Self::Live {
+            repo_root: repo_root.to_path_buf(),
+            config: Box::new(config),
+            output: repo_root.join("target").join("codex-tools-serve"),
+        }
+    }
+}
+
+/// One request the server answered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exchange {
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+}
+
+/// An HTTP/1.1 server for `GET` and `HEAD` requests that answers each connection on its
+/// own thread.
+pub struct PreviewServer {
+    listener: TcpListener,
+    root: SiteRoot,
+    /// The most recent live build; live requests take turns on it.
+    live: Mutex<LiveBuild>,
+}
+
+/// What the live artifact was built from, and how that build went.
+#[derive(Default)]
+struct LiveBuild {
+    /// `None` until the first build.
+    inputs: Option<Snapshot>,
+    cache: SourceCache,
+    error: Option<String>,
+}
+
+impl PreviewServer {
+    /// Binds `addr`; port `0` picks a free port.
+    pub fn bind(addr: impl ToSocketAddrs, mut root: SiteRoot) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        if let SiteRoot::Live { config, .. } = &mut root {
+            config.pages_base_url = format!("http://{}", listener.local_addr()?);
+        }
+        Ok(Self {
+            listener,
+            root,
+            live: Mutex::default(),
+        })
+    }
+
+    /// The site root, such as `http://127.0.0.1:8000`.
+    pub fn url(&self) -> io::Result<String> {
+        Ok(format!("http://{}", self.listener.local_addr()?))
+    }
+
+    /// Answers requests until accepting a connection fails, calling `log` after each one.
+    pub fn run(&self, mut log: impl FnMut(&Exchange)) -> io::Result<()> {
+        let (sender, exchanges) = mpsc::channel();
+        thread::scope(|scope| {
+            let accept = scope.spawn(move || -> io::Result<()> {
+                for stream in self.listener.incoming() {
+                    let stream = stream?;
+                    let sender = sender.clone();
+                    scope.spawn(move || {
+                        // A client hanging up mid-request must not stop the server.
+                        if let Ok(exchange) = self.handle(stream) {
+                            let _ = sender.send(exchange);
+                        }
+                    });
+                }
+                Ok(())
+            });
+            // Logging stays on this thread; the loop ends once the accept loop has stopped.
+            for exchange in exchanges {
+                log(&exchange);
+            }
+            accept
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        })
+    }
+
+    /// Accepts and answers one connection.
+    pub fn accept(&self) -> io::Result<Exchange> {
+        let (stream, _) = self.listener.accept()?;
+        self.handle(stream)
+    }
+
+    fn handle(&self, mut stream: TcpStream) -> io::Result<Exchange> {
+        // An idle client, such as a browser preconnect, gives up its thread eventually.
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        // Drain the headers; the preview needs none of them.
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 2 {
+            line.clear();
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+        let response = match method {
+            "GET" | "HEAD" => self.respond(target),
+            "" => Response::text(400, "malformed request line\n"),
+            _ => {
+                let mut response = Response::text(405, "only GET and HEAD are supported\n");
+                response.header("Allow", "GET, HEAD");
+                response
+            }
+        };
+        response.write_to(&mut stream, method == "HEAD")?;
+        Ok(Exchange {
+            method: method.to_string(),
+            path: target.to_string(),
+            status: response.status,
+        })
+    }
+
+    fn respond(&self, target: &str) -> Response {
+        // Held until the response is read, so no request sees a live build in progress.
+        let _build;
+        let dir = match &self.root {
+            SiteRoot::Artifact(dir) => dir,
+            SiteRoot::Live {
+                repo_root,
+                config,
+                output,
+            } => {
+                let mut live = self.live.lock().unwrap_or_else(PoisonError::into_inner);
+                let inputs = Snapshot::take(config, &live_inputs(repo_root));
+                let includes = live.cache.includes();
+                if live.inputs.as_ref() != Some(&inputs) || includes.refresh() != includes {
+                    let build = build_pages(repo_root, config, output, Some(&mut live.cache));
+                    live.error = build.err().map(|err| format!("{err}\n"));
+                    live.inputs = Some(inputs);
+                }
+                if let Some(error) = &live.error {
+                    return Response::text(500, error);
+                }
+                _build = live;
+                output
+            }
+        };
+        let Some(relative) = site_path(target) else {
+            return Response::text(400, "invalid path\n");
+        };
+        let mut path = dir.join(&relative);
+        if path.is_dir() {
+            path = path.join("index.md");
+        }
+        let Ok(body) = fs::read(&path) else {
+            return Response::text(404, &format!("{target} not found\n"));
+        };
+
+        let mut response = Response {
+            status: 200,
+            headers: Vec::new(),
+            body,
+        };
+        let headers = fs::read_to_string(dir.join(STATIC_CONFIG))
+            .ok()
+            .and_then(|json| serde_json::from_str::<StaticConfig>(&json).ok())
+            .unwrap_or_default();
+        let site_path = path.strip_prefix(dir).unwrap_or(&path);
+        for header in headers.headers_for(&site_path.to_string_lossy().replace('\\', "/")) {
+            response.header(&header.key, &header.value);
+        }
+        if !response.has_header("Content-Type") {
+            response.header("Content-Type", content_type(&path));
+        }
+        response
+    }
+}
+
+/// Repository files the artifact is built from besides the persona and scenario sources.
+fn live_inputs(repo_root: &Path) -> Vec<PathBuf> {
+    PAGES_MANIFEST
+        .iter()
+        .filter(|file| file.origin == Origin::Repository)
+        .map(|file| repo_root.join(file.source))
+        .chain([repo_root.join("docs").join("INSTRUCTIONS.md")])
+        .collect()
+}
+
+/// The file path for a request target: the percent-decoded path without its query,
+/// or `None` when it could escape the served directory.
+fn site_path(target: &str) -> Option<PathBuf> {
+    let path = target.split(['?', '#']).next()?;
+    let decoded = percent_decode(path.strip_prefix('/')?)?;
+    let relative = PathBuf::from(decoded);
+    relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+        .then_some(relative)
+}
+
+fn percent_decode(input: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut rest = input.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn text(status: u16, body: &str) -> Self {
+        let mut response = Self {
+            status,
+            headers: Vec::new(),
+            body: body.as_bytes().to_vec(),
+        };
+        response.header("Content-Type", "text/plain; charset=utf-8");
+        response
+    }
+
+    fn header(&mut self, key: &str, value: &str) {
+        self.headers.push((key.to_string(), value.to_string()));
+    }
+
+    fn has_header(&self, key: &str) -> bool {
+        self.headers
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case(key))
+    }
+
+    fn write_to(&self, stream: &mut impl Write, head_only: bool) -> io::Result<()> {
+        let reason = match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Internal Server Error",
+        };
+        write!(stream, "HTTP/1.1 {} {reason}\r\n", self.status)?;
+        for (key, value) in &self.headers {
+            write!(stream, "{key}: {value}\r\n")?;
+        }
+        write!(
+            stream,
+            "Content-Length: {}\r\nConnection: close\r\n\r\n",
+            self.body.len()
+        )?;
+        if !head_only {
+            stream.write_all(&self.body)?;
+        }
+        stream.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Origin, PAGES_MANIFEST};
+    use std::io::Read;
+    use std::thread;
+    use tempfile::tempdir;
+
+    /// Sends `request` to a server that answers exactly one connection.
+    fn send(server: &PreviewServer, request: &str) -> (Exchange, String) {
+        let url = server.url().expect("url");
+        let addr = url.trim_start_matches("http://").to_string();
+        let request = request.to_string();
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).expect("connect");
+            stream.write_all(request.as_bytes()).expect("send");
+            let mut response = String::new();
+            stream.read_to_string(&mut response).expect("receive");
+            response
+        });
+        let exchange = server.accept().expect("accept");
+        (exchange, client.join().expect("client"))
+    }
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().expect("parent")).expect("dir");
+        fs::write(path, contents).expect("write");
+    }
+
+    #[test]
+    fn serves_artifact_files_with_static_json_headers() {
+        let tmp = tempdir().expect("tempdir");
+        write(tmp.path(), "scripts/Setup.sh", "echo hi\n");
+        write(tmp.path(), "index.md", "# Home\n");
+        write(tmp.path(), "docs/My Notes.md", "notes\n");
+        write(
+            tmp.path(),
+            STATIC_CONFIG,
+            r#"{"headers": [{"source": "/scripts/Setup.sh", "headers": [{"key": "Cache-Control", "value": "no-cache"}]}]}"#,
+        );
+        let server = PreviewServer::bind("127.0.0.1:0", SiteRoot::Artifact(tmp.path().into()))
+            .expect("bind");
+
+        let (exchange, response) = send(
+            &server,
+            "GET /scripts/Setup.sh?v=1 HTTP/1.1\r\nHost: x\r\n\r\n",
+        );
+        assert_eq!(exchange.status, 200);
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+        assert!(
+            response.contains("Cache-Control: no-cache\r\n"),
+            "{response}"
+        );
+        assert!(response.contains("Content-Type: text/plain; charset=utf-8\r\n"));
+        assert!(response.ends_with("\r\n\r\necho hi\n"), "{response}");
+
+        let (_, response) = send(&server, "HEAD / HTTP/1.1\r\n\r\n");
+        assert!(response.contains("Content-Type: text/markdown; charset=utf-8\r\n"));
+        assert!(response.contains("Content-Length: 7\r\n"), "{response}");
+        assert!(response.ends_with("\r\n\r\n"), "{response}");
+
+        let (_, response) = send(&server, "GET /docs/My%20Notes.md HTTP/1.1\r\n\r\n");
+        assert!(response.ends_with("notes\n"), "{response}");
+    }
+
+    #[test]
+    fn rejects_missing_files_traversal_and_other_methods() {
+        let tmp = tempdir().expect("tempdir");
+        let site = tmp.path().join("site");
+        write(&site, "index.md", "# Home\n");
+        write(tmp.path(), "secret.txt", "secret\n");
+        let server = PreviewServer::bind("127.0.0.1:0", SiteRoot::Artifact(site)).expect("bind");
+
+        assert_eq!(
+            send(&server, "GET /missing.md HTTP/1.1\r\n\r\n").0.status,
+            404
+        );
+        for target in ["/../secret.txt", "/%2e%2e/secret.txt", "relative"] {
+            let (exchange, response) = send(&server, &format!("GET {target} HTTP/1.1\r\n\r\n"));
+            assert_eq!(exchange.status, 400, "{target}");
+            assert!(!response.contains("secret\n"));
+        }
+        let (exchange, response) = send(&server, "POST / HTTP/1.1\r\n\r\n");
+        assert_eq!(exchange.status, 405);
+        assert!(response.contains("Allow: GET, HEAD\r\n"));
+    }
+
+    #[test]
+    fn idle_connections_do_not_block_other_clients() {
+        let tmp = tempdir().expect("tempdir");
+        write(tmp.path(), "index.md", "# Home\n");
+        let server = PreviewServer::bind("127.0.0.1:0", SiteRoot::Artifact(tmp.path().into()))
+            .expect("bind");
+        let addr = server.url().expect("url").replace("http://", "");
+        thread::spawn(move || server.run(|_| {}));
+
+        let _idle = TcpStream::connect(&addr).expect("connect");
+        let mut stream = TcpStream::connect(&addr).expect("connect");
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .expect("timeout");
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\n").expect("send");
+        let mut response = String::new();
+        stream.read_to_string(&mut response).expect("receive");
+        assert!(response.ends_with("# Home\n"), "{response}");
+    }
+
+    #[test]
+    fn live_mode_rebuilds_with_catalog_uris_pointing_at_the_server() {
+        let tmp = tempdir().expect("tempdir");
+        let repo = tmp.path();
+        for file in PAGES_MANIFEST {
+            if file.origin == Origin::Repository {
+                write(repo, file.source, "content\n");
+            }
+        }
+        write(
+            repo,
+            "personas/ONE.md",
+            "---\nid: one\nname: One\n---\n# One\n",
+        );
+        fs::create_dir(repo.join("scenarios")).expect("scenarios");
+        let root = SiteRoot::live(repo, RepoConfig::defaults(repo));
+        let server = PreviewServer::bind("127.0.0.1:0", root).expect("bind");
+        let url = server.url().expect("url");
+
+        let (_, response) = send(&server, "GET /personas.json HTTP/1.1\r\n\r\n");
+        assert!(
+            response.contains(&format!("\"uri\": \"{url}/personas/ONE.md\"")),
+            "{response}"
+        );
+        assert!(response.contains("Content-Type: application/json; charset=utf-8\r\n"));
+
+        // Unchanged sources are not rebuilt.
+        let output = repo.join("target/codex-tools-serve");
+        fs::remove_file(output.join(".nojekyll")).expect("remove");
+        assert_eq!(send(&server, "GET / HTTP/1.1\r\n\r\n").0.status, 200);
+        assert!(!output.join(".nojekyll").exists());
+
+        write(repo, "snippets/note.md", "Noted.\n");
+        write(
+            repo,
+            "personas/ONE.md",
+            "---\nid: one\nname: One\n---\n# Edited\n{{> snippets/note.md}}\n",
+        );
+        let (_, response) = send(&server, "GET /personas/ONE.md HTTP/1.1\r\n\r\n");
+        assert!(response.ends_with("# Edited\nNoted.\n"), "{response}");
+        assert!(output.join(".nojekyll").exists());
+
+        // Included snippets count as sources too.
+        write(repo, "snippets/note.md", "Noted again.\n");
+        let (_, response) = send(&server, "GET /personas/ONE.md HTTP/1.1\r\n\r\n");
+        assert!(response.ends_with("# Edited\nNoted again.\n"), "{response}");
+
+        write(repo, "personas/TWO.md", "no front matter\n");
+        let (exchange, response) = send(&server, "GET /personas.json HTTP/1.1\r\n\r\n");
+        assert_eq!(exchange.status, 500);
+        assert!(response.contains("TWO.md"), "{response}");
+    }
+}
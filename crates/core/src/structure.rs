@@ -0,0 +1,418 @@
+use crate::Document;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Level-two sections every persona must contain, in order (SPECIFICATION §3.4).
+pub const PERSONA_SECTIONS: &[&str] = &[
+    "Role Snapshot",
+    "Responsibilities Checklist",
+    "When to Switch Away",
+    "Required Artifacts",
+    "Collaboration Signals",
+];
+
+/// Level-two sections every scenario must contain, in order (SPECIFICATION §4.3).
+pub const SCENARIO_SECTIONS: &[&str] = &[
+    "Goal",
+    "When to Use",
+    "Inputs",
+    "Execution Steps",
+    "Prompt Template",
+];
+
+const PROMPT_TEMPLATE: &str = "Prompt Template";
+
+/// An ATX Markdown heading found in a document body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    pub level: usize,
+    pub text: String,
+    /// 1-based line number in the source file.
+    pub line: usize,
+}
+
+/// Extracts ATX headings from `body`, skipping fenced code blocks.
+///
+/// `first_line` is the file line on which `body` starts so reported lines
+/// point into the original Markdown file rather than the body slice.
+pub fn parse_headings(body: &str, first_line: usize) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut fence: Option<&str> = None;
+    for (offset, line) in body.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") {
+            fence = Some("```");
+            continue;
+        }
+        if trimmed.starts_with("~~~") {
+            fence = Some("~~~");
+            continue;
+        }
+        if let Some(heading) = parse_heading(line) {
+            headings.push(Heading {
+                line: first_line + offset,
+                ..heading
+            });
+        }
+    }
+    headings
+}
+
+fn parse_heading(line: &str) -> Option<Heading> {
+    // Up to three spaces of indentation are allowed before an ATX heading.
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = &line[indent..];
+    let level = rest.len() - rest.trim_start_matches('#').len();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &rest[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let text = rest.trim().trim_end_matches('#').trim_end();
+    Some(Heading {
+        level,
+        text: text.to_string(),
+        line: 0,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StructureIssueKind {
+    MissingTitle { expected: String },
+    TitleMismatch { expected: String, found: String },
+    MissingSection { section: String },
+    MisorderedSection { section: String, after: String },
+    EmptySection { section: String },
+    EmptyPromptTemplate,
+}
+
+impl fmt::Display for StructureIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StructureIssueKind::MissingTitle { expected } => {
+                write!(f, "missing level-one heading `# {expected}`")
+            }
+            StructureIssueKind::TitleMismatch { expected, found } => {
+                write!(
+                    f,
+                    "level-one heading `# {found}` does not match name `{expected}`"
+                )
+            }
+            StructureIssueKind::MissingSection { section } => {
+                write!(f, "missing required section `## {section}`")
+            }
+            StructureIssueKind::MisorderedSection { section, after } => {
+                write!(f, "section `## {section}` must appear after `## {after}`")
+            }
+            StructureIssueKind::EmptySection { section } => {
+                write!(f, "section `## {section}` is empty")
+            }
+            StructureIssueKind::EmptyPromptTemplate => {
+                write!(f, "`## {PROMPT_TEMPLATE}` contains no prompt text")
+            }
+        }
+    }
+}
+
+/// A structural problem in a Markdown body, anchored to a file line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructureIssue {
+    /// The file holding `line` when it is not the checked file, as for a section a
+    /// persona inherits from its parent.
+    pub path: Option<PathBuf>,
+    pub line: usize,
+    pub kind: StructureIssueKind,
+}
+
+impl fmt::Display for StructureIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}:{}: {}", path.display(), self.line, self.kind),
+            None => write!(f, "line {}: {}", self.line, self.kind),
+        }
+    }
+}
+
+/// Checks a persona body against the minimum instruction blocks of SPECIFICATION §3.4.
+///
+/// Every problem is reported rather than stopping at the first one.
+pub fn check_persona_structure(name: &str, body: &str, first_line: usize) -> Vec<StructureIssue> {
+    let document = Document::parse(body, first_line);
+    let mut issues = check_title(name, &document, first_line);
+    issues.extend(check_sections(PERSONA_SECTIONS, &document));
+    issues
+}
+
+/// Checks a scenario body against the minimum instruction blocks of SPECIFICATION §4.3.
+///
+/// Besides missing or misordered blocks, required sections without content and
+/// a Prompt Template that only holds empty code fences are reported.
+pub fn check_scenario_structure(name: &str, body: &str, first_line: usize) -> Vec<StructureIssue> {
+    let document = Document::parse(body, first_line);
+    let mut issues = check_title(name, &document, first_line);
+    issues.extend(check_sections(SCENARIO_SECTIONS, &document));
+    issues.extend(check_section_content(SCENARIO_SECTIONS, &document));
+    issues
+}
+
+pub(crate) fn check_title(
+    name: &str,
+    document: &Document,
+    first_line: usize,
+) -> Vec<StructureIssue> {
+    match &document.title {
+        None => vec![StructureIssue {
+            path: None,
+            line: document
+                .headings
+                .first()
+                .map_or(first_line, |heading| heading.line),
+            kind: StructureIssueKind::MissingTitle {
+                expected: name.to_string(),
+            },
+        }],
+        Some(title) if title.text != name => vec![StructureIssue {
+            path: None,
+            line: title.line,
+            kind: StructureIssueKind::TitleMismatch {
+                expected: name.to_string(),
+                found: title.text.clone(),
+            },
+        }],
+        Some(_) => Vec::new(),
+    }
+}
+
+pub(crate) fn check_sections(required: &[&str], document: &Document) -> Vec<StructureIssue> {
+    let mut issues = Vec::new();
+    let end_line = document.end_line;
+    let sections = document
+        .sections
+        .iter()
+        .map(|section| &section.heading)
+        .collect::<Vec<_>>();
+
+    let mut previous: Option<(usize, &str)> = None;
+    for (index, section) in required.iter().enumerate() {
+        let Some(position) = sections.iter().position(|heading| heading.text == *section) else {
+            // Anchor a missing section where the next required section starts.
+            let line = required[index + 1..]
+                .iter()
+                .find_map(|next| sections.iter().find(|heading| heading.text == *next))
+                .map_or(end_line, |heading| heading.line);
+            issues.push(StructureIssue {
+                path: None,
+                line,
+                kind: StructureIssueKind::MissingSection {
+                    section: section.to_string(),
+                },
+            });
+            continue;
+        };
+        match previous {
+            Some((previous_position, after)) if position < previous_position => {
+                issues.push(StructureIssue {
+                    path: None,
+                    line: sections[position].line,
+                    kind: StructureIssueKind::MisorderedSection {
+                        section: section.to_string(),
+                        after: after.to_string(),
+                    },
+                });
+            }
+            _ => previous = Some((position, section)),
+        }
+    }
+    issues
+}
+
+fn check_section_content(required: &[&str], document: &Document) -> Vec<StructureIssue> {
+    let mut issues = Vec::new();
+    for section in &document.sections {
+        let heading = &section.heading;
+        if !required.contains(&heading.text.as_str()) {
+            continue;
+        }
+        if heading.text == PROMPT_TEMPLATE {
+            let has_prompt = section.content.lines().any(|line| {
+                let trimmed = line.trim();
+                !trimmed.is_empty() && !trimmed.starts_with("```") && !trimmed.starts_with("~~~")
+            });
+            if !has_prompt {
+                issues.push(StructureIssue {
+                    path: None,
+                    line: heading.line,
+                    kind: StructureIssueKind::EmptyPromptTemplate,
+                });
+            }
+        } else if section.is_empty() {
+            issues.push(StructureIssue {
+                path: None,
+                line: heading.line,
+                kind: StructureIssueKind::EmptySection {
+                    section: heading.text.clone(),
+                },
+            });
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = "# Delivery Engineer\n\n## Role Snapshot\nText\n\n## Responsibilities Checklist\n- a\n\n## When to Switch Away\n- b\n\n## Required Artifacts\n- c\n\n## Collaboration Signals\n- d\n";
+
+    #[test]
+    fn parses_headings_outside_code_fences() {
+        let body = "# Title\n```\n# not a heading\n```\n## Section ##\n#nospace\n";
+        let headings = parse_headings(body, 10);
+        assert_eq!(
+            headings,
+            vec![
+                Heading {
+                    level: 1,
+                    text: "Title".into(),
+                    line: 10
+                },
+                Heading {
+                    level: 2,
+                    text: "Section".into(),
+                    line: 14
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn accepts_valid_persona() {
+        assert!(check_persona_structure("Delivery Engineer", VALID, 1).is_empty());
+    }
+
+    #[test]
+    fn reports_title_mismatch() {
+        let issues = check_persona_structure("Delivery Lead", VALID, 11);
+        assert_eq!(
+            issues,
+            vec![StructureIssue {
+                path: None,
+                line: 11,
+                kind: StructureIssueKind::TitleMismatch {
+                    expected: "Delivery Lead".into(),
+                    found: "Delivery Engineer".into(),
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_every_missing_section() {
+        let body = "## Role Snapshot\n\n## Required Artifacts\n";
+        let issues = check_persona_structure("One", body, 5);
+        let kinds = issues
+            .iter()
+            .map(|issue| (issue.line, issue.kind.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                (5, "missing level-one heading `# One`".to_string()),
+                (
+                    7,
+                    "missing required section `## Responsibilities Checklist`".to_string()
+                ),
+                (
+                    7,
+                    "missing required section `## When to Switch Away`".to_string()
+                ),
+                (
+                    7,
+                    "missing required section `## Collaboration Signals`".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_misordered_section() {
+        let body = VALID.replace(
+            "## Required Artifacts\n- c\n\n## Collaboration Signals\n- d\n",
+            "## Collaboration Signals\n- d\n\n## Required Artifacts\n- c\n",
+        );
+        let issues = check_persona_structure("Delivery Engineer", &body, 1);
+        assert_eq!(
+            issues,
+            vec![StructureIssue {
+                path: None,
+                line: 12,
+                kind: StructureIssueKind::MisorderedSection {
+                    section: "Collaboration Signals".into(),
+                    after: "Required Artifacts".into(),
+                },
+            }]
+        );
+    }
+
+    const SCENARIO: &str = "# Audit\n\n## Goal\nShip.\n\n## When to Use\n- Always.\n\n## Inputs\n- Repo.\n\n## Execution Steps\n1. Run.\n\n## Prompt Template\n```\nAct as the auditor.\n```\n";
+
+    #[test]
+    fn accepts_valid_scenario() {
+        assert!(check_scenario_structure("Audit", SCENARIO, 1).is_empty());
+    }
+
+    #[test]
+    fn reports_empty_sections_and_prompt_template() {
+        let body = SCENARIO
+            .replace("- Repo.\n", "")
+            .replace("Act as the auditor.\n", "");
+        let issues = check_scenario_structure("Audit", &body, 1);
+        assert_eq!(
+            issues,
+            vec![
+                StructureIssue {
+                    path: None,
+                    line: 9,
+                    kind: StructureIssueKind::EmptySection {
+                        section: "Inputs".into()
+                    },
+                },
+                StructureIssue {
+                    path: None,
+                    line: 14,
+                    kind: StructureIssueKind::EmptyPromptTemplate,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_missing_scenario_blocks() {
+        let body = "# Audit\n\n## Goal\nShip.\n";
+        let issues = check_scenario_structure("Audit", body, 1);
+        let sections = issues
+            .iter()
+            .map(|issue| issue.kind.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sections,
+            vec![
+                "missing required section `## When to Use`",
+                "missing required section `## Inputs`",
+                "missing required section `## Execution Steps`",
+                "missing required section `## Prompt Template`",
+            ]
+        );
+    }
+}
@@ -0,0 +1,368 @@
+use crate::{CatalogError, SourceSpan, parse_front_matter};
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TemplateError {
+    #[error("included snippet {} not found", snippet.display())]
+    MissingSnippet { snippet: PathBuf },
+    #[error("included snippet {} is outside {}", snippet.display(), root.display())]
+    OutsideRoot { snippet: PathBuf, root: PathBuf },
+    #[error("include cycle: {}", render_chain(chain))]
+    IncludeCycle { chain: Vec<PathBuf> },
+    #[error("unknown template variable `{name}`")]
+    UnknownVariable { name: String },
+}
+
+fn render_chain(chain: &[PathBuf]) -> String {
+    chain
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+/// What `{{> path}}` and `{{ name }}` directives resolve against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateContext {
+    /// Include paths are relative to this directory, normally the repository root.
+    pub root: PathBuf,
+    pub variables: BTreeMap<String, String>,
+}
+
+impl TemplateContext {
+    /// The variables available to every persona and scenario: `pages_base_url` and `id`.
+    pub fn for_source(root: &Path, base_url: &str, id: &str) -> Self {
+        let variables = [
+            ("pages_base_url", base_url.trim_end_matches('/')),
+            ("id", id),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+        Self {
+            root: root.to_path_buf(),
+            variables,
+        }
+    }
+}
+
+/// Expands the template directives in `text`, the body of `path` starting on line `first_line`.
+///
+/// `{{> snippets/name.md}}` is replaced by the named file (itself expanded, without its
+/// trailing newline), which must lie inside `context.root`, and `{{ name }}` by a variable
+/// from `context`. A backslash before `{{` keeps the braces literally, and fenced code
+/// blocks and inline code spans are copied verbatim, so examples such as
+/// `${{ secrets.TOKEN }}` need no escaping.
+pub fn render_template(
+    path: &Path,
+    text: &str,
+    first_line: usize,
+    context: &TemplateContext,
+) -> Result<String, CatalogError> {
+    expand_template(path, text, first_line, context).map(|(body, _)| body)
+}
+
+/// Like [`render_template`], but also returns the snippets the text includes, directly or
+/// through other snippets, in path order.
+pub(crate) fn expand_template(
+    path: &Path,
+    text: &str,
+    first_line: usize,
+    context: &TemplateContext,
+) -> Result<(String, Vec<PathBuf>), CatalogError> {
+    let mut stack = vec![fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())];
+    let mut includes = Vec::new();
+    let body = expand(path, text, first_line, context, &mut stack, &mut includes)?;
+    includes.sort();
+    includes.dedup();
+    Ok((body, includes))
+}
+
+fn expand(
+    path: &Path,
+    text: &str,
+    first_line: usize,
+    context: &TemplateContext,
+    stack: &mut Vec<PathBuf>,
+    includes: &mut Vec<PathBuf>,
+) -> Result<String, CatalogError> {
+    let mut output = String::with_capacity(text.len());
+    let code = code_ranges(text);
+    let mut cursor = 0;
+    while let Some(found) = text[cursor..].find("{{") {
+        let start = cursor + found;
+        if let Some(range) = code.iter().find(|range| range.contains(&start)) {
+            output.push_str(&text[cursor..range.end]);
+            cursor = range.end;
+            continue;
+        }
+        if text[..start].ends_with('\\') {
+            output.push_str(&text[cursor..start - 1]);
+            output.push_str("{{");
+            cursor = start + 2;
+            continue;
+        }
+        let Some(len) = text[start + 2..]
+            .find("}}")
+            .filter(|&len| !text[start + 2..start + 2 + len].contains('\n'))
+        else {
+            output.push_str(&text[cursor..start + 2]);
+            cursor = start + 2;
+            continue;
+        };
+        output.push_str(&text[cursor..start]);
+        let directive = text[start + 2..start + 2 + len].trim();
+        let fail = |source| CatalogError::template(path, span_at(text, start, first_line), source);
+
+        if let Some(include) = directive.strip_prefix('>') {
+            let snippet = context.root.join(include.trim());
+            let Ok(canonical) = fs::canonicalize(&snippet) else {
+                return Err(fail(TemplateError::MissingSnippet { snippet }));
+            };
+            let root = fs::canonicalize(&context.root).unwrap_or_else(|_| context.root.clone());
+            if !canonical.starts_with(&root) {
+                return Err(fail(TemplateError::OutsideRoot { snippet, root }));
+            }
+            if let Some(position) = stack.iter().position(|seen| *seen == canonical) {
+                let mut chain = stack[position..].to_vec();
+                chain.push(canonical);
+                return Err(fail(TemplateError::IncludeCycle { chain }));
+            }
+            let content = fs::read_to_string(&snippet)
+                .map_err(|source| CatalogError::io(&snippet, source))?
+                .replace("\r\n", "\n");
+            stack.push(canonical);
+            let expanded = expand(&snippet, &content, 1, context, stack, includes)?;
+            includes.push(snippet);
+            stack.pop();
+            output.push_str(expanded.trim_end_matches('\n'));
+        } else {
+            let value = context.variables.get(directive).ok_or_else(|| {
+                fail(TemplateError::UnknownVariable {
+                    name: directive.to_string(),
+                })
+            })?;
+            output.push_str(value);
+        }
+        cursor = start + 2 + len + 2;
+    }
+    output.push_str(&text[cursor..]);
+    Ok(output)
+}
+
+/// Byte ranges of `text` holding code: fenced blocks (an unclosed fence runs to the end)
+/// and inline code spans, which never cross a blank line.
+fn code_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut fence: Option<(&str, usize)> = None;
+    let mut prose_start = 0;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let end = offset + line.len();
+        match fence {
+            Some((marker, start)) => {
+                if trimmed.starts_with(marker) {
+                    ranges.push(start..end);
+                    fence = None;
+                    prose_start = end;
+                }
+            }
+            None => {
+                if let Some(marker) = ["```", "~~~"]
+                    .into_iter()
+                    .find(|marker| trimmed.starts_with(marker))
+                {
+                    inline_code(text, prose_start..offset, &mut ranges);
+                    fence = Some((marker, offset));
+                } else if trimmed.trim_end().is_empty() {
+                    inline_code(text, prose_start..end, &mut ranges);
+                    prose_start = end;
+                }
+            }
+        }
+        offset = end;
+    }
+    match fence {
+        Some((_, start)) => ranges.push(start..text.len()),
+        None => inline_code(text, prose_start..text.len(), &mut ranges),
+    }
+    ranges
+}
+
+/// Adds the code spans in `paragraph`: a run of backticks up to the next run of the same length.
+fn inline_code(text: &str, paragraph: Range<usize>, ranges: &mut Vec<Range<usize>>) {
+    let bytes = &text.as_bytes()[..paragraph.end];
+    let run_at = |index: usize| bytes[index..].iter().take_while(|&&b| b == b'`').count();
+    let mut index = paragraph.start;
+    while index < bytes.len() {
+        if bytes[index] != b'`' {
+            index += 1;
+            continue;
+        }
+        let open = run_at(index);
+        let mut close = index + open;
+        let end = loop {
+            match bytes[close..].iter().position(|&b| b == b'`') {
+                Some(found) => {
+                    let run = run_at(close + found);
+                    if run == open {
+                        break Some(close + found + run);
+                    }
+                    close += found + run;
+                }
+                None => break None,
+            }
+        };
+        match end {
+            Some(end) => {
+                ranges.push(index..end);
+                index = end;
+            }
+            // An unmatched run is literal text.
+            None => index += open,
+        }
+    }
+}
+
+fn span_at(text: &str, offset: usize, first_line: usize) -> SourceSpan {
+    let line_start = text[..offset].rfind('\n').map_or(0, |index| index + 1);
+    let line_end = text[offset..]
+        .find('\n')
+        .map_or(text.len(), |index| offset + index);
+    SourceSpan {
+        line: first_line + text[..line_start].matches('\n').count(),
+        column: text[line_start..offset].chars().count() + 1,
+        snippet: Some(text[line_start..line_end].to_string()),
+    }
+}
+
+/// Returns the Markdown file at `path` with its body replaced by `body`, keeping the
+/// front matter as written. Used to publish rendered personas and scenarios.
+pub fn published_markdown(path: &Path, body: &str) -> Result<String, CatalogError> {
+    let content = fs::read_to_string(path).map_err(|source| CatalogError::io(path, source))?;
+    let front_matter = parse_front_matter(&content)
+        .map_err(|source| CatalogError::front_matter(path, &content, source))?;
+    let normalized = content.replace("\r\n", "\n");
+    let header = &normalized[..normalized.len() - front_matter.body.len()];
+    Ok(format!("{header}{body}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+    use tempfile::tempdir;
+
+    #[test]
+    fn expands_includes_and_variables() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let root = tmp.path();
+        fs::create_dir(root.join("snippets"))?;
+        fs::write(
+            root.join("snippets").join("loop.md"),
+            "1. Run `cargo test` for {{ id }}.\n{{> snippets/footer.md}}\n",
+        )?;
+        fs::write(root.join("snippets").join("footer.md"), "2. Report.\n")?;
+        let context = TemplateContext::for_source(root, "https://example.invalid/", "dev");
+
+        let (rendered, includes) = expand_template(
+            &root.join("DEV.md"),
+            "## Loop\n{{> snippets/loop.md}}\n\nDocs: {{pages_base_url}}/docs \\{{ literal }}\n",
+            4,
+            &context,
+        )?;
+        assert_eq!(
+            rendered,
+            "## Loop\n1. Run `cargo test` for dev.\n2. Report.\n\nDocs: https://example.invalid/docs {{ literal }}\n"
+        );
+        assert_eq!(
+            includes,
+            [
+                root.join("snippets/footer.md"),
+                root.join("snippets/loop.md")
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reports_missing_snippets_cycles_and_unknown_variables() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let root = tmp.path();
+        fs::create_dir(root.join("snippets"))?;
+        fs::write(root.join("snippets").join("a.md"), "{{> snippets/b.md}}\n")?;
+        fs::write(
+            root.join("snippets").join("b.md"),
+            "x\n  {{> snippets/a.md}}\n",
+        )?;
+        let context = TemplateContext::for_source(root, "https://example.invalid", "dev");
+        let path = root.join("DEV.md");
+
+        let err =
+            render_template(&path, "# Dev\n{{> snippets/gone.md}}\n", 5, &context).unwrap_err();
+        let CatalogError::Template { span, source, .. } = err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!((span.line, span.column), (6, 1));
+        assert!(matches!(source, TemplateError::MissingSnippet { .. }));
+
+        let err = render_template(&path, "{{> snippets/a.md}}", 1, &context).unwrap_err();
+        let CatalogError::Template {
+            path: at,
+            span,
+            source: TemplateError::IncludeCycle { chain },
+        } = err
+        else {
+            panic!("unexpected error: {err}");
+        };
+        assert!(at.ends_with("b.md"));
+        assert_eq!((span.line, span.column), (2, 3));
+        assert_eq!(chain.len(), 3);
+        assert_eq!(chain.first(), chain.last());
+
+        fs::write(root.join("secret.md"), "token\n")?;
+        let nested =
+            TemplateContext::for_source(&root.join("snippets"), "https://example.invalid", "dev");
+        let err = render_template(&path, "{{> ../secret.md}}", 1, &nested).unwrap_err();
+        let CatalogError::Template {
+            source: TemplateError::OutsideRoot { snippet, .. },
+            ..
+        } = err
+        else {
+            panic!("unexpected error: {err}");
+        };
+        assert!(snippet.ends_with("../secret.md"));
+
+        let err = render_template(&path, "Hi {{ nope }}", 1, &context).unwrap_err();
+        assert!(
+            err.to_string()
+                .ends_with("unknown template variable `nope`"),
+            "{err}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn leaves_code_blocks_and_spans_alone() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir()?;
+        let context = TemplateContext::for_source(tmp.path(), "https://example.invalid", "dev");
+        let body = "# Dev {{ id }}\n\n```yaml\ntoken: ${{ secrets.TOKEN }}\n{{> snippets/none.md}}\n```\n\nUse `{{ name }}` or ``{{#each `x`}}``.\n~~~\n{{ nope }}\n~~~\nA stray ` before {{ id }}.\n";
+        let rendered = render_template(&tmp.path().join("DEV.md"), body, 1, &context)?;
+        assert_eq!(
+            rendered,
+            "# Dev dev\n\n```yaml\ntoken: ${{ secrets.TOKEN }}\n{{> snippets/none.md}}\n```\n\nUse `{{ name }}` or ``{{#each `x`}}``.\n~~~\n{{ nope }}\n~~~\nA stray ` before dev.\n"
+        );
+
+        let unclosed = "```\n{{ nope }}\n";
+        assert_eq!(
+            render_template(&tmp.path().join("DEV.md"), unclosed, 1, &context)?,
+            unclosed
+        );
+        Ok(())
+    }
+}
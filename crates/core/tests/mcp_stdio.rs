@@ -0,0 +1,55 @@
+use serde_json::Value;
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use tempfile::tempdir;
+
+#[test]
+fn personas_mcp_answers_piped_requests() {
+    let tmp = tempdir().expect("tempdir");
+    let personas_dir = tmp.path().join("personas");
+    fs::create_dir(&personas_dir).expect("personas dir");
+    fs::write(
+        personas_dir.join("ONE.md"),
+        "---\nid: one\nname: One\n---\n# One\n",
+    )
+    .expect("persona");
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_personas-mcp"))
+        .arg("--repo-root")
+        .arg(tmp.path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("spawn personas-mcp");
+
+    let requests = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"test","version":"0"}}}"#,
+        r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"prompts/get","params":{"name":"one"}}"#,
+    ];
+    {
+        let mut stdin = child.stdin.take().expect("stdin");
+        for request in requests {
+            writeln!(stdin, "{request}").expect("write request");
+        }
+    }
+
+    let output = child.wait_with_output().expect("wait");
+    assert!(output.status.success());
+    let responses = String::from_utf8(output.stdout)
+        .expect("utf8")
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).expect("json"))
+        .collect::<Vec<_>>();
+
+    assert_eq!(responses.len(), 2);
+    assert_eq!(
+        responses[0]["result"]["serverInfo"]["name"],
+        "personas-core"
+    );
+    assert_eq!(
+        responses[1]["result"]["messages"][0]["content"]["text"],
+        "# One\n"
+    );
+}